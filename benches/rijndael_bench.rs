@@ -1,5 +1,5 @@
 use criterion::{criterion_group, criterion_main, Criterion};
-use simple_rijndael::{impls::RijndaelCbc, paddings::ZeroPadding};
+use simple_rijndael::{impls::RijndaelCbc, modes, paddings::ZeroPadding, rijndael::Rijndael};
 
 const OSU_KEY: &[u8; 32] = b"osu!-scoreburgr---------20210520";
 const OSU_IV: [u8; 32] = [
@@ -52,5 +52,47 @@ fn osu_encrypt_benchmark(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, osu_encrypt_benchmark, osu_decrypt_benchmark);
+/// Single blocks with an expanded key, to compare the table layouts:
+/// `cargo bench -- --save-baseline default` then
+/// `cargo bench --features small-tables -- --baseline default`.
+fn block_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("block_benchmark");
+    let rijndael = Rijndael::new(OSU_KEY, 32).unwrap();
+    let block = &OSU_DECRYPTED[..32];
+    group.bench_function("encrypt block", |b| {
+        b.iter(|| rijndael.encrypt(block).unwrap())
+    });
+    group.bench_function("decrypt block", |b| {
+        b.iter(|| rijndael.decrypt(block).unwrap())
+    });
+    group.bench_function("key expansion", |b| {
+        b.iter(|| Rijndael::new(OSU_KEY, 32).unwrap())
+    });
+    group.finish();
+}
+
+static OSU_RIJNDAEL: Rijndael = Rijndael::new_const(OSU_KEY, 32);
+
+/// Key expanded per call against the key expanded at compile time.
+fn const_key_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("const_key_benchmark");
+    group.bench_function("osu decrypt runtime key", |b| {
+        b.iter(|| {
+            let rijndael = Rijndael::new(OSU_KEY, 32).unwrap();
+            modes::cbc_decrypt(&rijndael, &OSU_IV, &OSU_CRYPTED).unwrap()
+        })
+    });
+    group.bench_function("osu decrypt const key", |b| {
+        b.iter(|| modes::cbc_decrypt(&OSU_RIJNDAEL, &OSU_IV, &OSU_CRYPTED).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    osu_encrypt_benchmark,
+    osu_decrypt_benchmark,
+    block_benchmark,
+    const_key_benchmark
+);
 criterion_main!(benches);
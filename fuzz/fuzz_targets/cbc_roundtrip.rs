@@ -0,0 +1,39 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use simple_rijndael::{
+    constants::VALID,
+    impls::RijndaelCbc,
+    paddings::{Padding, Pkcs7Padding, ZeroPadding},
+};
+
+// data: block size selector (1) || key size selector (1) || key || iv || plaintext
+fuzz_target!(|data: &[u8]| {
+    if data.len() < 2 {
+        return;
+    }
+    let block_size = VALID[data[0] as usize % VALID.len()];
+    let key_size = VALID[data[1] as usize % VALID.len()];
+    let data = &data[2..];
+    if data.len() < key_size + block_size {
+        return;
+    }
+    let (key, rest) = data.split_at(key_size);
+    let (iv, plaintext) = rest.split_at(block_size);
+
+    let cbc = RijndaelCbc::<Pkcs7Padding>::new(key, block_size).unwrap();
+    let encrypted = cbc.encrypt(iv, plaintext.to_vec()).unwrap();
+    assert_eq!(cbc.decrypt(iv, encrypted).unwrap(), plaintext);
+    let _ = cbc.decrypt(iv, plaintext.to_vec());
+
+    // zero padding is left in place by `decode`
+    let cbc = RijndaelCbc::<ZeroPadding>::new(key, block_size).unwrap();
+    let encrypted = cbc.encrypt(iv, plaintext.to_vec()).unwrap();
+    assert_eq!(
+        cbc.decrypt(iv, encrypted).unwrap(),
+        ZeroPadding::new(block_size)
+            .unwrap()
+            .encode(plaintext.to_vec())
+    );
+    let _ = cbc.decrypt(iv, plaintext.to_vec());
+});
@@ -0,0 +1,32 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use simple_rijndael::paddings::{
+    AnsiX923Padding, Iso10126Padding, Padding, Pkcs7Padding, ZeroPadding,
+};
+
+fn check<P: Padding>(block_size: usize, data: &[u8], round_trip: bool) {
+    let Ok(padding) = P::new(block_size) else {
+        assert_eq!(block_size, 0);
+        return;
+    };
+    let _ = padding.decode(data.to_vec());
+    let encoded = padding.encode(data.to_vec());
+    assert_eq!(encoded.len() % block_size, 0);
+    let decoded = padding.decode(encoded).unwrap();
+    if round_trip {
+        assert_eq!(decoded, data);
+    }
+}
+
+// data: block size (1) || bytes
+fuzz_target!(|data: &[u8]| {
+    let Some((&block_size, data)) = data.split_first() else {
+        return;
+    };
+    let block_size = block_size as usize;
+    check::<ZeroPadding>(block_size, data, false);
+    check::<Pkcs7Padding>(block_size, data, true);
+    check::<AnsiX923Padding>(block_size, data, true);
+    check::<Iso10126Padding>(block_size, data, true);
+});
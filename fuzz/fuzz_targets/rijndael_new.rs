@@ -0,0 +1,23 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use simple_rijndael::rijndael::Rijndael;
+
+// data: block size (1) || key
+fuzz_target!(|data: &[u8]| {
+    let Some((&block_size, key)) = data.split_first() else {
+        return;
+    };
+    let Ok(rijndael) = Rijndael::new(key, block_size as usize) else {
+        return;
+    };
+    let block: Vec<u8> = key
+        .iter()
+        .copied()
+        .cycle()
+        .take(rijndael.block_size())
+        .collect();
+    let encrypted = rijndael.encrypt(&block).unwrap();
+    assert_eq!(rijndael.decrypt(&encrypted).unwrap(), block);
+    assert!(rijndael.encrypt(&block[1..]).is_err());
+});
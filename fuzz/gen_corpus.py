@@ -0,0 +1,53 @@
+"""Writes the seed corpus from the osu! score vector of src/tests.rs.
+
+Run from this directory: python3 gen_corpus.py
+"""
+
+import os
+
+KEY = b"osu!-scoreburgr---------20210520"
+IV = bytes([
+    240, 124, 26, 154, 27, 186, 98, 170, 95, 190, 213, 103, 13, 128, 39, 59, 217, 84, 68, 144, 173,
+    93, 117, 132, 33, 213, 96, 154, 228, 231, 197, 162,
+])
+SCORE = (
+    b"c51aee56bb5195244252d190baa54b49:PurePeace :815772625adad639735a044e8aba9360:44:18:6:4:6:20:"
+    b"38645:26:False:F:64:False:0:210615064431:20210520"
+)
+DECRYPTED = SCORE + bytes([19] * 19)
+CRYPTED = bytes([
+    185, 209, 243, 33, 128, 187, 132, 199, 140, 189, 1, 129, 139, 66, 200, 37, 126, 34, 238, 100,
+    93, 124, 192, 252, 31, 121, 2, 130, 138, 123, 69, 66, 122, 208, 169, 15, 120, 22, 148, 19, 87,
+    203, 229, 139, 179, 89, 138, 56, 160, 239, 238, 254, 34, 119, 136, 225, 209, 226, 69, 117, 97,
+    49, 225, 198, 91, 192, 196, 12, 32, 235, 103, 141, 142, 168, 29, 102, 252, 65, 191, 137, 136,
+    170, 72, 165, 127, 49, 83, 201, 115, 26, 79, 200, 119, 28, 238, 34, 76, 25, 185, 164, 182, 254,
+    66, 148, 18, 36, 27, 36, 59, 165, 220, 95, 164, 115, 128, 12, 5, 161, 18, 236, 221, 167, 247,
+    104, 25, 29, 146, 225, 104, 140, 249, 175, 131, 158, 93, 143, 106, 146, 114, 220, 128, 33, 114,
+    191, 232, 103, 94, 169, 90, 105, 8, 85, 33, 140, 198, 71, 40, 65, 215, 29,
+])
+assert len(DECRYPTED) == len(CRYPTED) == 160
+
+# block and key size selectors index [16, 24, 32]
+CORPUS = {
+    "rijndael_new": {
+        "osu_key": bytes([32]) + KEY,
+        "osu_key_128": bytes([16]) + KEY[:16],
+        "osu_key_192": bytes([24]) + KEY[:24],
+    },
+    "cbc_roundtrip": {
+        "osu_score": bytes([2, 2]) + KEY + IV + SCORE,
+        "osu_padded": bytes([2, 2]) + KEY + IV + DECRYPTED,
+        "osu_crypted": bytes([2, 2]) + KEY + IV + CRYPTED,
+    },
+    "padding_decode": {
+        "osu_decrypted": bytes([32]) + DECRYPTED,
+        "osu_score": bytes([32]) + SCORE,
+        "osu_crypted": bytes([16]) + CRYPTED,
+    },
+}
+
+for target, seeds in CORPUS.items():
+    os.makedirs(os.path.join("corpus", target), exist_ok=True)
+    for name, data in seeds.items():
+        with open(os.path.join("corpus", target, name), "wb") as f:
+            f.write(data)
@@ -0,0 +1,117 @@
+/* Generated by cbindgen from src/capi.rs, do not edit. */
+
+#ifndef SIMPLE_RIJNDAEL_H
+#define SIMPLE_RIJNDAEL_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#define RIJNDAEL_PADDING_ZERO 0
+
+#define RIJNDAEL_PADDING_PKCS7 1
+
+#define RIJNDAEL_PADDING_ANSI_X923 2
+
+#define RIJNDAEL_PADDING_ISO10126 3
+
+#define RIJNDAEL_OK 0
+
+#define RIJNDAEL_ERR_NULL_POINTER -1
+
+#define RIJNDAEL_ERR_KEY_SIZE -2
+
+#define RIJNDAEL_ERR_BLOCK_SIZE -3
+
+#define RIJNDAEL_ERR_IV_SIZE -4
+
+#define RIJNDAEL_ERR_DATA_SIZE -5
+
+#define RIJNDAEL_ERR_PADDING -6
+
+#define RIJNDAEL_ERR_BUFFER_TOO_SMALL -7
+
+#define RIJNDAEL_ERR_OTHER -8
+
+// Opaque cipher handle, created by `rijndael_cbc_new`.
+typedef struct RijndaelCbcHandle RijndaelCbcHandle;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Creates a CBC cipher, `block_size` and `key_len` are 16, 24 or 32 and
+// `padding` one of `RIJNDAEL_PADDING_*`. The handle is stored in `*handle`
+// and must be released with `rijndael_cbc_free`.
+//
+// # Safety
+//
+// `key` must point to `key_len` readable bytes and `handle` must be writable.
+int32_t rijndael_cbc_new(const uint8_t *key,
+                         size_t key_len,
+                         size_t block_size,
+                         uint32_t padding,
+                         struct RijndaelCbcHandle **handle);
+
+// Releases a handle from `rijndael_cbc_new`, null is ignored.
+//
+// # Safety
+//
+// `handle` must be null or a handle that was not freed yet.
+void rijndael_cbc_free(struct RijndaelCbcHandle *handle);
+
+// Ciphertext length for `input_len` bytes of plaintext, 0 for a null handle
+// or when the length doesn't fit in `size_t`.
+//
+// # Safety
+//
+// `handle` must be null or a live handle.
+size_t rijndael_cbc_encrypt_size(const struct RijndaelCbcHandle *handle, size_t input_len);
+
+// Upper bound of the plaintext length for `input_len` bytes of ciphertext,
+// the exact length is returned by `rijndael_cbc_decrypt`.
+//
+// # Safety
+//
+// `handle` must be null or a live handle.
+size_t rijndael_cbc_decrypt_size(const struct RijndaelCbcHandle *handle, size_t input_len);
+
+// Encrypts `input` into `output`, the ciphertext length is stored in
+// `*output_len`. Returns `RIJNDAEL_ERR_BUFFER_TOO_SMALL` (with the required
+// length in `*output_len`) when `output_capacity` is not enough.
+//
+// # Safety
+//
+// `handle` must be a live handle, `iv`, `input` and `output` must point to
+// `iv_len`, `input_len` and `output_capacity` bytes and `output_len` must be
+// writable.
+int32_t rijndael_cbc_encrypt(const struct RijndaelCbcHandle *handle,
+                             const uint8_t *iv,
+                             size_t iv_len,
+                             const uint8_t *input,
+                             size_t input_len,
+                             uint8_t *output,
+                             size_t output_capacity,
+                             size_t *output_len);
+
+// Decrypts `input` into `output`, see `rijndael_cbc_encrypt`.
+//
+// # Safety
+//
+// Same as `rijndael_cbc_encrypt`.
+int32_t rijndael_cbc_decrypt(const struct RijndaelCbcHandle *handle,
+                             const uint8_t *iv,
+                             size_t iv_len,
+                             const uint8_t *input,
+                             size_t input_len,
+                             uint8_t *output,
+                             size_t output_capacity,
+                             size_t *output_len);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* SIMPLE_RIJNDAEL_H */
@@ -0,0 +1,183 @@
+use std::{
+    io,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use crate::{impls::IncrementalCipher, Errors};
+
+const READ_CHUNK_SIZE: usize = 4096;
+
+#[inline(always)]
+fn invalid_data(err: Errors) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+/// `AsyncWrite` adapter that runs everything written to it through an
+/// incremental cipher (e.g. `RijndaelCbc::encryptor`) before passing it on to
+/// the inner writer. The final block is padded and written on shutdown.
+#[derive(Debug)]
+pub struct CbcWriter<W, C> {
+    inner: W,
+    state: Option<C>,
+    pending: Vec<u8>,
+    written: usize,
+}
+
+impl<W, C> CbcWriter<W, C>
+where
+    W: AsyncWrite + Unpin,
+    C: IncrementalCipher + Unpin,
+{
+    #[inline(always)]
+    pub fn new(inner: W, state: C) -> Self {
+        Self {
+            inner,
+            state: Some(state),
+            pending: Vec::new(),
+            written: 0,
+        }
+    }
+
+    #[inline(always)]
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    #[inline(always)]
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    fn poll_drain(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while self.written < self.pending.len() {
+            let n =
+                ready!(Pin::new(&mut self.inner).poll_write(cx, &self.pending[self.written..]))?;
+            if n == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            self.written += n;
+        }
+        self.pending.clear();
+        self.written = 0;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<W, C> AsyncWrite for CbcWriter<W, C>
+where
+    W: AsyncWrite + Unpin,
+    C: IncrementalCipher + Unpin,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        ready!(this.poll_drain(cx))?;
+        let state = match this.state.as_mut() {
+            Some(state) => state,
+            None => return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into())),
+        };
+        state.update(buf, &mut this.pending).map_err(invalid_data)?;
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_drain(cx))?;
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_drain(cx))?;
+        if let Some(state) = this.state.take() {
+            state.finalize(&mut this.pending).map_err(invalid_data)?;
+            ready!(this.poll_drain(cx))?;
+        }
+        Pin::new(&mut this.inner).poll_shutdown(cx)
+    }
+}
+
+/// `AsyncRead` adapter that runs everything read from the inner reader through
+/// an incremental cipher (e.g. `RijndaelCbc::decryptor`). The cipher is
+/// finalized when the inner reader reaches end of file.
+#[derive(Debug)]
+pub struct CbcReader<R, C> {
+    inner: R,
+    state: Option<C>,
+    pending: Vec<u8>,
+    read: usize,
+    chunk: Vec<u8>,
+}
+
+impl<R, C> CbcReader<R, C>
+where
+    R: AsyncRead + Unpin,
+    C: IncrementalCipher + Unpin,
+{
+    #[inline(always)]
+    pub fn new(inner: R, state: C) -> Self {
+        Self {
+            inner,
+            state: Some(state),
+            pending: Vec::new(),
+            read: 0,
+            chunk: vec![0; READ_CHUNK_SIZE],
+        }
+    }
+
+    #[inline(always)]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    #[inline(always)]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R, C> AsyncRead for CbcReader<R, C>
+where
+    R: AsyncRead + Unpin,
+    C: IncrementalCipher + Unpin,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            if this.read < this.pending.len() {
+                let n = buf.remaining().min(this.pending.len() - this.read);
+                buf.put_slice(&this.pending[this.read..(this.read + n)]);
+                this.read += n;
+                return Poll::Ready(Ok(()));
+            }
+            this.pending.clear();
+            this.read = 0;
+
+            let state = match this.state.as_mut() {
+                Some(state) => state,
+                None => return Poll::Ready(Ok(())),
+            };
+            let mut chunk = ReadBuf::new(&mut this.chunk);
+            ready!(Pin::new(&mut this.inner).poll_read(cx, &mut chunk))?;
+            if chunk.filled().is_empty() {
+                if let Some(state) = this.state.take() {
+                    state.finalize(&mut this.pending).map_err(invalid_data)?;
+                }
+            } else {
+                state
+                    .update(chunk.filled(), &mut this.pending)
+                    .map_err(invalid_data)?;
+            }
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use std::{
+    fs,
+    io::{self, Read, Write},
+    path::PathBuf,
+    process::ExitCode,
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use clap::{Parser, ValueEnum};
+use simple_rijndael::{
+    cipher::{Algorithm, Cipher, CipherSpec},
+    modes::Mode,
+    osu,
+    paddings::PaddingKind,
+};
+
+/// Encrypt or decrypt files with Rijndael.
+///
+/// Keys and IVs are hex by default, or prefixed with `hex:`, `base64:` or
+/// `file:` (raw bytes of a file).
+#[derive(Debug, Parser)]
+#[command(name = "rijndael", version)]
+struct Args {
+    #[arg(value_enum)]
+    operation: Operation,
+    /// Input file, stdin if omitted or `-`
+    input: Option<PathBuf>,
+    /// Output file, stdout if omitted or `-`
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    #[arg(short, long, value_enum, default_value_t = Mode::Cbc)]
+    mode: Mode,
+    /// Block size in bytes, 16, 24 or 32
+    #[arg(short, long, default_value_t = 16, value_parser = clap::value_parser!(usize))]
+    block_size: usize,
+    /// Expected key size in bytes, checked against the given key
+    #[arg(long)]
+    key_size: Option<usize>,
+    /// pkcs7 in ecb and cbc, none in cfb and ofb if omitted
+    #[arg(short, long, value_enum)]
+    padding: Option<PaddingName>,
+    #[arg(short, long, required_unless_present = "osu_score")]
+    key: Option<String>,
+    /// Required except in ecb, which takes none
+    #[arg(short, long)]
+    iv: Option<String>,
+    /// Use the osu! score submission format: the key is derived from
+    /// `--osu-version`, block size 32 with PKCS#7 padding, and the ciphertext
+    /// is base64 text as sent in the `score` form field
+    #[arg(long, requires_all = ["osu_version", "iv"], conflicts_with_all = ["key", "mode", "block_size", "padding"])]
+    osu_score: bool,
+    /// The `osuver` form field
+    #[arg(long)]
+    osu_version: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Operation {
+    Encrypt,
+    Decrypt,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum PaddingName {
+    None,
+    Zero,
+    Pkcs7,
+    #[value(name = "ansix923")]
+    AnsiX923,
+    Iso10126,
+}
+
+fn parse_bytes(value: &str) -> Result<Vec<u8>, String> {
+    if let Some(path) = value.strip_prefix("file:") {
+        fs::read(path).map_err(|e| format!("{}: {}", path, e))
+    } else if let Some(data) = value.strip_prefix("base64:") {
+        STANDARD.decode(data).map_err(|e| e.to_string())
+    } else {
+        hex::decode(value.strip_prefix("hex:").unwrap_or(value)).map_err(|e| e.to_string())
+    }
+}
+
+fn read_input(input: &Option<PathBuf>) -> io::Result<Vec<u8>> {
+    match input {
+        Some(path) if path.as_os_str() != "-" => fs::read(path),
+        _ => {
+            let mut buf = Vec::new();
+            io::stdin().read_to_end(&mut buf)?;
+            Ok(buf)
+        }
+    }
+}
+
+fn write_output(output: &Option<PathBuf>, data: &[u8]) -> io::Result<()> {
+    match output {
+        Some(path) if path.as_os_str() != "-" => fs::write(path, data),
+        _ => io::stdout().write_all(data),
+    }
+}
+
+fn cipher(args: &Args, key: &[u8]) -> Result<Cipher, String> {
+    let padding = match args.padding {
+        Some(PaddingName::None) => None,
+        Some(PaddingName::Zero) => Some("zero"),
+        Some(PaddingName::Pkcs7) => Some("pkcs7"),
+        Some(PaddingName::AnsiX923) => Some("ansix923"),
+        Some(PaddingName::Iso10126) => Some("iso10126"),
+        None if args.mode.is_block_mode() => Some("pkcs7"),
+        None => None,
+    };
+    if padding.is_some() && !args.mode.is_block_mode() {
+        return Err(format!("{} mode takes no padding", args.mode));
+    }
+    let spec = CipherSpec {
+        algorithm: Algorithm::Rijndael {
+            block_size: args.block_size,
+        },
+        mode: args.mode,
+        padding: padding
+            .map(|name| PaddingKind::from_name(name, args.block_size))
+            .transpose()
+            .map_err(|e| e.to_string())?,
+    };
+    Cipher::from_spec(spec, key).map_err(|e| e.to_string())
+}
+
+fn osu_score(args: &Args, iv: &[u8], input: Vec<u8>) -> Result<Vec<u8>, String> {
+    let version = args.osu_version.as_deref().unwrap_or_default();
+    let input = String::from_utf8(input).map_err(|e| e.to_string())?;
+    match args.operation {
+        // only the line ending, trailing spaces are the client flags
+        Operation::Encrypt => {
+            let line = input
+                .strip_suffix("\r\n")
+                .or_else(|| input.strip_suffix('\n'))
+                .unwrap_or(&input);
+            osu::encrypt_field(line, iv, version)
+        }
+        Operation::Decrypt => osu::decrypt_field(&input, &STANDARD.encode(iv), version),
+    }
+    .map(String::into_bytes)
+    .map_err(|e| e.to_string())
+}
+
+fn check_iv(iv: &[u8], size: usize) -> Result<(), String> {
+    if iv.len() != size {
+        return Err(format!("iv is {} bytes, expected {}", iv.len(), size));
+    }
+    Ok(())
+}
+
+fn run(args: &Args) -> Result<(), String> {
+    let iv = args.iv.as_deref().map(parse_bytes).transpose()?;
+    let output = if args.osu_score {
+        let iv = iv.unwrap_or_default();
+        check_iv(&iv, osu::BLOCK_SIZE)?;
+        let input = read_input(&args.input).map_err(|e| e.to_string())?;
+        osu_score(args, &iv, input)?
+    } else {
+        let key = parse_bytes(args.key.as_deref().unwrap_or_default())?;
+        if let Some(key_size) = args.key_size {
+            if key.len() != key_size {
+                return Err(format!("key is {} bytes, expected {}", key.len(), key_size));
+            }
+        }
+        let cipher = cipher(args, &key)?;
+        let iv = match (args.mode, iv) {
+            (Mode::Ecb, None) => Vec::new(),
+            (Mode::Ecb, Some(_)) => return Err("ecb mode takes no iv".to_string()),
+            (_, None) => return Err(format!("--iv is required in {} mode", args.mode)),
+            (_, Some(iv)) => iv,
+        };
+        check_iv(&iv, cipher.iv_size())?;
+        let input = read_input(&args.input).map_err(|e| e.to_string())?;
+        match args.operation {
+            Operation::Encrypt => cipher.encrypt(&iv, &input),
+            Operation::Decrypt => cipher.decrypt(&iv, &input),
+        }
+        .map_err(|e| e.to_string())?
+    };
+    write_output(&args.output, &output).map_err(|e| e.to_string())
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("rijndael: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
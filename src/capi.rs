@@ -0,0 +1,235 @@
+use std::{ptr, slice};
+
+use crate::{
+    impls::RijndaelCbc,
+    paddings::{AnsiX923Padding, Iso10126Padding, Padding, PaddingKind, Pkcs7Padding, ZeroPadding},
+    Errors,
+};
+
+// C ABI, declared in `include/simple_rijndael.h` (generated with
+// `cbindgen --config cbindgen.toml`). Functions return `RIJNDAEL_OK` or a
+// negative `RIJNDAEL_ERR_*` code and write into caller buffers sized with
+// `rijndael_cbc_encrypt_size` / `rijndael_cbc_decrypt_size`.
+
+pub const RIJNDAEL_PADDING_ZERO: u32 = 0;
+pub const RIJNDAEL_PADDING_PKCS7: u32 = 1;
+pub const RIJNDAEL_PADDING_ANSI_X923: u32 = 2;
+pub const RIJNDAEL_PADDING_ISO10126: u32 = 3;
+
+pub const RIJNDAEL_OK: i32 = 0;
+pub const RIJNDAEL_ERR_NULL_POINTER: i32 = -1;
+pub const RIJNDAEL_ERR_KEY_SIZE: i32 = -2;
+pub const RIJNDAEL_ERR_BLOCK_SIZE: i32 = -3;
+pub const RIJNDAEL_ERR_IV_SIZE: i32 = -4;
+pub const RIJNDAEL_ERR_DATA_SIZE: i32 = -5;
+pub const RIJNDAEL_ERR_PADDING: i32 = -6;
+pub const RIJNDAEL_ERR_BUFFER_TOO_SMALL: i32 = -7;
+pub const RIJNDAEL_ERR_OTHER: i32 = -8;
+
+#[inline(always)]
+fn error_code(err: Errors) -> i32 {
+    match err {
+        Errors::InvalidKeySize { .. } => RIJNDAEL_ERR_KEY_SIZE,
+        Errors::InvalidBlockSize { .. } => RIJNDAEL_ERR_BLOCK_SIZE,
+        Errors::InvalidIvSize { .. } => RIJNDAEL_ERR_IV_SIZE,
+        Errors::InvalidDataSize { .. } => RIJNDAEL_ERR_DATA_SIZE,
+        Errors::InvalidPadding => RIJNDAEL_ERR_PADDING,
+        _ => RIJNDAEL_ERR_OTHER,
+    }
+}
+
+/// Opaque cipher handle, created by `rijndael_cbc_new`.
+pub struct RijndaelCbcHandle(RijndaelCbc<PaddingKind>);
+
+/// `len` bytes at `data`, `data` may be null when `len` is 0.
+#[inline(always)]
+unsafe fn bytes<'a>(data: *const u8, len: usize) -> Option<&'a [u8]> {
+    match (data.is_null(), len) {
+        (_, 0) => Some(&[]),
+        (true, _) => None,
+        (false, _) => Some(slice::from_raw_parts(data, len)),
+    }
+}
+
+/// Copies `result` into `output`, `*output_len` is set to the result length
+/// even when `output_capacity` is too small.
+#[inline(always)]
+unsafe fn write_output(
+    result: Result<Vec<u8>, Errors>,
+    output: *mut u8,
+    output_capacity: usize,
+    output_len: *mut usize,
+) -> i32 {
+    let result = match result {
+        Ok(result) => result,
+        Err(err) => return error_code(err),
+    };
+    *output_len = result.len();
+    if result.len() > output_capacity {
+        return RIJNDAEL_ERR_BUFFER_TOO_SMALL;
+    }
+    if !result.is_empty() {
+        if output.is_null() {
+            return RIJNDAEL_ERR_NULL_POINTER;
+        }
+        ptr::copy_nonoverlapping(result.as_ptr(), output, result.len());
+    }
+    RIJNDAEL_OK
+}
+
+/// Creates a CBC cipher, `block_size` and `key_len` are 16, 24 or 32 and
+/// `padding` one of `RIJNDAEL_PADDING_*`. The handle is stored in `*handle`
+/// and must be released with `rijndael_cbc_free`.
+///
+/// # Safety
+///
+/// `key` must point to `key_len` readable bytes and `handle` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn rijndael_cbc_new(
+    key: *const u8,
+    key_len: usize,
+    block_size: usize,
+    padding: u32,
+    handle: *mut *mut RijndaelCbcHandle,
+) -> i32 {
+    if handle.is_null() {
+        return RIJNDAEL_ERR_NULL_POINTER;
+    }
+    let Some(key) = bytes(key, key_len) else {
+        return RIJNDAEL_ERR_NULL_POINTER;
+    };
+    let padding = match padding {
+        RIJNDAEL_PADDING_ZERO => ZeroPadding::new(block_size).map(PaddingKind::Zero),
+        RIJNDAEL_PADDING_PKCS7 => Pkcs7Padding::new(block_size).map(PaddingKind::Pkcs7),
+        RIJNDAEL_PADDING_ANSI_X923 => AnsiX923Padding::new(block_size).map(PaddingKind::AnsiX923),
+        RIJNDAEL_PADDING_ISO10126 => Iso10126Padding::new(block_size).map(PaddingKind::Iso10126),
+        _ => return RIJNDAEL_ERR_PADDING,
+    };
+    let cipher = padding.and_then(|padding| RijndaelCbc::with_padding(key, padding));
+    match cipher {
+        Ok(cipher) => {
+            *handle = Box::into_raw(Box::new(RijndaelCbcHandle(cipher)));
+            RIJNDAEL_OK
+        }
+        Err(err) => error_code(err),
+    }
+}
+
+/// Releases a handle from `rijndael_cbc_new`, null is ignored.
+///
+/// # Safety
+///
+/// `handle` must be null or a handle that was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn rijndael_cbc_free(handle: *mut RijndaelCbcHandle) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
+
+/// Ciphertext length for `input_len` bytes of plaintext, 0 for a null handle
+/// or when the length doesn't fit in `size_t`.
+///
+/// # Safety
+///
+/// `handle` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn rijndael_cbc_encrypt_size(
+    handle: *const RijndaelCbcHandle,
+    input_len: usize,
+) -> usize {
+    let Some(handle) = handle.as_ref() else {
+        return 0;
+    };
+    let block_size = handle.0.rijndael.block_size();
+    let blocks = match handle.0.padding {
+        PaddingKind::Zero(_) => input_len.div_ceil(block_size),
+        _ => input_len / block_size + 1,
+    };
+    blocks.checked_mul(block_size).unwrap_or(0)
+}
+
+/// Upper bound of the plaintext length for `input_len` bytes of ciphertext,
+/// the exact length is returned by `rijndael_cbc_decrypt`.
+///
+/// # Safety
+///
+/// `handle` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn rijndael_cbc_decrypt_size(
+    handle: *const RijndaelCbcHandle,
+    input_len: usize,
+) -> usize {
+    match handle.is_null() {
+        true => 0,
+        false => input_len,
+    }
+}
+
+/// Encrypts `input` into `output`, the ciphertext length is stored in
+/// `*output_len`. Returns `RIJNDAEL_ERR_BUFFER_TOO_SMALL` (with the required
+/// length in `*output_len`) when `output_capacity` is not enough.
+///
+/// # Safety
+///
+/// `handle` must be a live handle, `iv`, `input` and `output` must point to
+/// `iv_len`, `input_len` and `output_capacity` bytes and `output_len` must be
+/// writable.
+#[no_mangle]
+pub unsafe extern "C" fn rijndael_cbc_encrypt(
+    handle: *const RijndaelCbcHandle,
+    iv: *const u8,
+    iv_len: usize,
+    input: *const u8,
+    input_len: usize,
+    output: *mut u8,
+    output_capacity: usize,
+    output_len: *mut usize,
+) -> i32 {
+    let (Some(handle), Some(iv), Some(input)) =
+        (handle.as_ref(), bytes(iv, iv_len), bytes(input, input_len))
+    else {
+        return RIJNDAEL_ERR_NULL_POINTER;
+    };
+    if output_len.is_null() {
+        return RIJNDAEL_ERR_NULL_POINTER;
+    }
+    write_output(
+        handle.0.encrypt(iv, input.to_vec()),
+        output,
+        output_capacity,
+        output_len,
+    )
+}
+
+/// Decrypts `input` into `output`, see `rijndael_cbc_encrypt`.
+///
+/// # Safety
+///
+/// Same as `rijndael_cbc_encrypt`.
+#[no_mangle]
+pub unsafe extern "C" fn rijndael_cbc_decrypt(
+    handle: *const RijndaelCbcHandle,
+    iv: *const u8,
+    iv_len: usize,
+    input: *const u8,
+    input_len: usize,
+    output: *mut u8,
+    output_capacity: usize,
+    output_len: *mut usize,
+) -> i32 {
+    let (Some(handle), Some(iv), Some(input)) =
+        (handle.as_ref(), bytes(iv, iv_len), bytes(input, input_len))
+    else {
+        return RIJNDAEL_ERR_NULL_POINTER;
+    };
+    if output_len.is_null() {
+        return RIJNDAEL_ERR_NULL_POINTER;
+    }
+    write_output(
+        handle.0.decrypt(iv, input.to_vec()),
+        output,
+        output_capacity,
+        output_len,
+    )
+}
@@ -0,0 +1,92 @@
+//! Encrypt-then-MAC over `RijndaelCbc`, laid out like AES_CBC_HMAC_SHA2
+//! (draft-mcgrew-aead-aes-cbc-hmac-sha2) with empty associated data: the
+//! first half of the master key is the MAC key, the second half the cipher
+//! key, and the tag is HMAC-SHA256(IV || ciphertext || AL) cut to `tag_size`
+//! bytes, AL being the associated data length in bits as 64 bit big endian
+//! (0). With a 32 byte master key, 16 byte blocks, PKCS#7 and 16 byte tags,
+//! IV || `encrypt` output is AEAD_AES_128_CBC_HMAC_SHA_256's.
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::{impls::RijndaelCbc, paddings::Padding, types::IntoIv, Errors, Size};
+
+pub const MAX_TAG_SIZE: usize = 32;
+/// Shorter tags are rejected.
+pub const MIN_TAG_SIZE: usize = 16;
+
+#[derive(Debug)]
+pub struct RijndaelCbcHmac<P: Padding> {
+    pub cbc: RijndaelCbc<P>,
+    mac_key: Vec<u8>,
+    tag_size: usize,
+}
+
+impl<P> RijndaelCbcHmac<P>
+where
+    P: Padding,
+{
+    /// `master_key` is twice the cipher key size (32, 48 or 64 bytes).
+    #[inline(always)]
+    pub fn new(master_key: &[u8], block_size: usize, tag_size: usize) -> Result<Self, Errors> {
+        if !(MIN_TAG_SIZE..=MAX_TAG_SIZE).contains(&tag_size) {
+            return Err(Errors::InvalidTagSize {
+                expected: Size::Between(MIN_TAG_SIZE, MAX_TAG_SIZE),
+                actual: tag_size,
+            });
+        }
+        if ![32, 48, 64].contains(&master_key.len()) {
+            return Err(Errors::InvalidKeySize {
+                expected: Size::Between(32, 64),
+                actual: master_key.len(),
+            });
+        }
+        let (mac_key, key) = master_key.split_at(master_key.len() / 2);
+        Ok(Self {
+            cbc: RijndaelCbc::new(key, block_size)?,
+            mac_key: mac_key.into(),
+            tag_size,
+        })
+    }
+
+    #[inline(always)]
+    pub fn tag_size(&self) -> usize {
+        self.tag_size
+    }
+
+    #[inline(always)]
+    fn mac(&self, iv: &[u8], ciphertext: &[u8]) -> Hmac<Sha256> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.mac_key).expect("HMAC accepts keys of any length");
+        mac.update(iv);
+        mac.update(ciphertext);
+        mac.update(&0u64.to_be_bytes());
+        mac
+    }
+
+    /// Returns the ciphertext followed by the tag.
+    pub fn encrypt<'a>(&self, iv: impl IntoIv<'a>, source: Vec<u8>) -> Result<Vec<u8>, Errors> {
+        let iv = iv.into_iv(self.cbc.rijndael.block_size())?;
+        let mut result = self.cbc.encrypt(iv, source)?;
+        let tag = self.mac(&iv, &result).finalize().into_bytes();
+        result.extend_from_slice(&tag[..self.tag_size]);
+        Ok(result)
+    }
+
+    /// Verifies the tag in constant time before decrypting, nothing is
+    /// decrypted if it doesn't match.
+    pub fn decrypt<'a>(&self, iv: impl IntoIv<'a>, mut cipher: Vec<u8>) -> Result<Vec<u8>, Errors> {
+        let iv = iv.into_iv(self.cbc.rijndael.block_size())?;
+        if cipher.len() < self.tag_size + self.cbc.rijndael.block_size() {
+            return Err(Errors::InvalidDataSize {
+                expected: Size::AtLeast(self.tag_size + self.cbc.rijndael.block_size()),
+                actual: cipher.len(),
+            });
+        }
+        let tag = cipher.split_off(cipher.len() - self.tag_size);
+        self.mac(&iv, &cipher)
+            .verify_truncated_left(&tag)
+            .map_err(|_| Errors::AuthenticationFailed)?;
+        self.cbc.decrypt(iv, cipher)
+    }
+}
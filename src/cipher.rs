@@ -0,0 +1,214 @@
+use std::{fmt, str::FromStr};
+
+use crate::{
+    modes::{self, Mode},
+    paddings::{Padding, PaddingKind, Pkcs7Padding},
+    rijndael::Rijndael,
+    Errors, Size,
+};
+
+// Ciphers chosen at runtime by identifier, `<algorithm>-<bits>-<mode>` with an
+// optional `/<padding>`, case insensitive:
+//
+// - `rijndael-128`, `rijndael-192`, `rijndael-256`: the bits are the block
+//   size (like mcrypt), any valid key size
+// - `aes-128`, `aes-192`, `aes-256`: the bits are the key size, 16 byte blocks
+// - modes `ecb`, `cbc`, `cfb` and `ofb`, CFB and OFB feed back whole blocks
+// - paddings `none`, `zero`, `pkcs7`, `ansix923` and `iso10126`, PKCS#7 if
+//   omitted in ECB and CBC; CFB and OFB take only `none`
+//
+// e.g. `rijndael-256-cbc/zero` (osu!) or `aes-128-cbc/pkcs7`.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    Rijndael { block_size: usize },
+    Aes { key_size: usize },
+}
+
+impl Algorithm {
+    #[inline(always)]
+    pub fn block_size(&self) -> usize {
+        match self {
+            Algorithm::Rijndael { block_size } => *block_size,
+            Algorithm::Aes { .. } => 16,
+        }
+    }
+}
+
+/// A parsed cipher identifier.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CipherSpec {
+    pub algorithm: Algorithm,
+    pub mode: Mode,
+    /// `None` for `none`
+    pub padding: Option<PaddingKind>,
+}
+
+/// `none` or a `PaddingKind` name, checked against the mode.
+#[inline(always)]
+pub(crate) fn parse_padding(
+    name: &str,
+    mode: Mode,
+    block_size: usize,
+) -> Result<Option<PaddingKind>, Errors> {
+    let padding = match name {
+        "none" => None,
+        name => Some(PaddingKind::from_name(name, block_size)?),
+    };
+    if !mode.is_block_mode() && padding.is_some() {
+        return Err(Errors::InvalidPadding);
+    }
+    Ok(padding)
+}
+
+impl FromStr for CipherSpec {
+    type Err = Errors;
+
+    /// Unknown algorithms or sizes give `InvalidAlgorithm`, unknown modes
+    /// `InvalidMode` and unknown paddings `InvalidPadding`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_ascii_lowercase();
+        let (cipher, padding) = match s.split_once('/') {
+            Some((cipher, padding)) => (cipher, Some(padding)),
+            None => (s.as_str(), None),
+        };
+        let mut parts = cipher.splitn(3, '-');
+        let (Some(name), Some(bits), Some(mode)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(Errors::InvalidAlgorithm);
+        };
+        let size = match bits {
+            "128" => 16,
+            "192" => 24,
+            "256" => 32,
+            _ => return Err(Errors::InvalidAlgorithm),
+        };
+        let algorithm = match name {
+            "rijndael" => Algorithm::Rijndael { block_size: size },
+            "aes" => Algorithm::Aes { key_size: size },
+            _ => return Err(Errors::InvalidAlgorithm),
+        };
+        let mode: Mode = mode.parse()?;
+        let padding = match padding {
+            Some(name) => parse_padding(name, mode, algorithm.block_size())?,
+            None if mode.is_block_mode() => Some(PaddingKind::Pkcs7(Pkcs7Padding::new(
+                algorithm.block_size(),
+            )?)),
+            None => None,
+        };
+        Ok(Self {
+            algorithm,
+            mode,
+            padding,
+        })
+    }
+}
+
+impl fmt::Display for CipherSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.algorithm {
+            Algorithm::Rijndael { block_size } => write!(f, "rijndael-{}", block_size * 8)?,
+            Algorithm::Aes { key_size } => write!(f, "aes-{}", key_size * 8)?,
+        }
+        let padding = self.padding.as_ref().map_or("none", PaddingKind::name);
+        write!(f, "-{}/{}", self.mode, padding)
+    }
+}
+
+/// A `Rijndael` with the mode and padding of a `CipherSpec`.
+#[derive(Debug)]
+pub struct Cipher {
+    spec: CipherSpec,
+    rijndael: Rijndael,
+}
+
+impl Cipher {
+    /// `Cipher::new("aes-128-cbc/pkcs7", key)`.
+    #[inline(always)]
+    pub fn new(id: &str, key: &[u8]) -> Result<Self, Errors> {
+        Self::from_spec(id.parse()?, key)
+    }
+
+    /// Checks the fields like `CipherSpec::from_str`, the padding has to be
+    /// built for the algorithm's block size.
+    pub fn from_spec(spec: CipherSpec, key: &[u8]) -> Result<Self, Errors> {
+        let block_size = spec.algorithm.block_size();
+        if let Some(padding) = &spec.padding {
+            if !spec.mode.is_block_mode() {
+                return Err(Errors::InvalidPadding);
+            }
+            if padding.size() != block_size {
+                return Err(Errors::InvalidBlockSize {
+                    expected: Size::Exactly(block_size),
+                    actual: padding.size(),
+                });
+            }
+        }
+        if let Algorithm::Aes { key_size } = spec.algorithm {
+            if key.len() != key_size {
+                return Err(Errors::InvalidKeySize {
+                    expected: Size::Exactly(key_size),
+                    actual: key.len(),
+                });
+            }
+        }
+        let rijndael = Rijndael::new(key, block_size)?;
+        Ok(Self { spec, rijndael })
+    }
+
+    #[inline(always)]
+    pub fn spec(&self) -> &CipherSpec {
+        &self.spec
+    }
+
+    /// 0 in ECB, one block otherwise.
+    #[inline(always)]
+    pub fn iv_size(&self) -> usize {
+        match self.spec.mode {
+            Mode::Ecb => 0,
+            _ => self.rijndael.block_size(),
+        }
+    }
+
+    #[inline(always)]
+    fn check_iv(&self, iv: &[u8]) -> Result<(), Errors> {
+        if iv.len() != self.iv_size() {
+            return Err(Errors::InvalidIvSize {
+                expected: Size::Exactly(self.iv_size()),
+                actual: iv.len(),
+            });
+        }
+        Ok(())
+    }
+
+    /// `iv` must be empty in ECB.
+    pub fn encrypt(&self, iv: &[u8], data: &[u8]) -> Result<Vec<u8>, Errors> {
+        self.check_iv(iv)?;
+        let block_size = self.rijndael.block_size();
+        let data = match &self.spec.padding {
+            Some(padding) => padding.encode(data.to_vec()),
+            None => data.to_vec(),
+        };
+        match self.spec.mode {
+            Mode::Ecb => modes::ecb_encrypt(&self.rijndael, &data),
+            Mode::Cbc => modes::cbc_encrypt(&self.rijndael, iv, &data),
+            Mode::Cfb => modes::cfb_encrypt(&self.rijndael, iv, block_size, &data),
+            Mode::Ofb => modes::ofb(&self.rijndael, iv, block_size, &data),
+        }
+    }
+
+    pub fn decrypt(&self, iv: &[u8], data: &[u8]) -> Result<Vec<u8>, Errors> {
+        self.check_iv(iv)?;
+        let block_size = self.rijndael.block_size();
+        let decrypted = match self.spec.mode {
+            Mode::Ecb => modes::ecb_decrypt(&self.rijndael, data)?,
+            Mode::Cbc => modes::cbc_decrypt(&self.rijndael, iv, data)?,
+            Mode::Cfb => modes::cfb_decrypt(&self.rijndael, iv, block_size, data)?,
+            Mode::Ofb => modes::ofb(&self.rijndael, iv, block_size, data)?,
+        };
+        match &self.spec.padding {
+            Some(padding) => padding.decode(decrypted),
+            None => Ok(decrypted),
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use std::fmt;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+pub use crate::modes::Mode;
+use crate::{
+    cipher::{self, Algorithm, Cipher, CipherSpec},
+    constants::VALID,
+    Errors, Size,
+};
+
+// Cipher settings for configuration files, e.g. in TOML
+//
+//     mode = "cbc"
+//     block_size = 32
+//     key_size = 32
+//     padding = "zero"
+//     key = "base64:b3N1IS1zY29yZWJ1cmdyLS0tLS0tLS0tMjAyMTA1MjA="
+//     iv = "hex:f07c1a9a1bba62aa5fbed5670d80273bd9544490ad5d758421d5609ae4e7c5a2"
+//
+// Keys and IVs are strings prefixed with `hex:` or `base64:` (hex if
+// unprefixed, like the `rijndael` command line tool).
+
+/// Bytes with the encoding they were written in, kept for serialization.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncodedBytes {
+    Hex(Vec<u8>),
+    Base64(Vec<u8>),
+}
+
+impl EncodedBytes {
+    #[inline(always)]
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            EncodedBytes::Hex(bytes) | EncodedBytes::Base64(bytes) => bytes,
+        }
+    }
+}
+
+impl fmt::Display for EncodedBytes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EncodedBytes::Hex(bytes) => write!(f, "hex:{}", hex::encode(bytes)),
+            EncodedBytes::Base64(bytes) => write!(f, "base64:{}", STANDARD.encode(bytes)),
+        }
+    }
+}
+
+impl std::str::FromStr for EncodedBytes {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.strip_prefix("base64:") {
+            Some(data) => STANDARD
+                .decode(data)
+                .map(EncodedBytes::Base64)
+                .map_err(|e| e.to_string()),
+            None => hex::decode(value.strip_prefix("hex:").unwrap_or(value))
+                .map(EncodedBytes::Hex)
+                .map_err(|e| e.to_string()),
+        }
+    }
+}
+
+impl Serialize for EncodedBytes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for EncodedBytes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+/// `padding` is `"none"`, `"zero"`, `"pkcs7"`, `"ansix923"` or `"iso10126"`,
+/// `cfb` and `ofb` only take `"none"`. `iv` is absent in ECB and one block
+/// otherwise.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CipherConfig {
+    pub mode: Mode,
+    pub block_size: usize,
+    pub key_size: usize,
+    pub padding: String,
+    pub key: EncodedBytes,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iv: Option<EncodedBytes>,
+}
+
+/// A cipher with its key, IV and padding bound, built by
+/// `CipherConfig::build`.
+pub trait ConfiguredCipher: fmt::Debug + Send + Sync {
+    fn encrypt(&self, data: &[u8]) -> Result<Vec<u8>, Errors>;
+    fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, Errors>;
+}
+
+#[derive(Debug)]
+struct Configured {
+    cipher: Cipher,
+    iv: Vec<u8>,
+}
+
+impl ConfiguredCipher for Configured {
+    #[inline(always)]
+    fn encrypt(&self, data: &[u8]) -> Result<Vec<u8>, Errors> {
+        self.cipher.encrypt(&self.iv, data)
+    }
+
+    #[inline(always)]
+    fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, Errors> {
+        self.cipher.decrypt(&self.iv, data)
+    }
+}
+
+impl CipherConfig {
+    /// Validates the config and expands the key.
+    pub fn build(&self) -> Result<Box<dyn ConfiguredCipher>, Errors> {
+        if !VALID.contains(&self.key_size) {
+            return Err(Errors::InvalidKeySize {
+                expected: Size::Rijndael,
+                actual: self.key_size,
+            });
+        }
+        let key = self.key.as_bytes();
+        if key.len() != self.key_size {
+            return Err(Errors::InvalidKeySize {
+                expected: Size::Exactly(self.key_size),
+                actual: key.len(),
+            });
+        }
+        let spec = CipherSpec {
+            algorithm: Algorithm::Rijndael {
+                block_size: self.block_size,
+            },
+            mode: self.mode,
+            padding: cipher::parse_padding(&self.padding, self.mode, self.block_size)?,
+        };
+        let cipher = Cipher::from_spec(spec, key)?;
+        let iv = self.iv.as_ref().map_or(&[][..], EncodedBytes::as_bytes);
+        if iv.len() != cipher.iv_size() {
+            return Err(Errors::InvalidIvSize {
+                expected: Size::Exactly(cipher.iv_size()),
+                actual: iv.len(),
+            });
+        }
+        Ok(Box::new(Configured {
+            cipher,
+            iv: iv.to_vec(),
+        }))
+    }
+}
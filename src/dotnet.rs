@@ -0,0 +1,237 @@
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+
+use crate::{
+    modes,
+    paddings::{AnsiX923Padding, Iso10126Padding, Padding, Pkcs7Padding, ZeroPadding},
+    rijndael::Rijndael,
+    Errors, Size,
+};
+
+// Compatibility with .NET Framework's `System.Security.Cryptography`
+// `RijndaelManaged` and `Rfc2898DeriveBytes`. Sizes are in bits, like the
+// .NET properties they mirror, sizes in errors are bytes (rounded up).
+
+/// `System.Security.Cryptography.CipherMode`, the modes `RijndaelManaged`
+/// supports.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CipherMode {
+    CBC,
+    ECB,
+    CFB,
+}
+
+/// `System.Security.Cryptography.PaddingMode`.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaddingMode {
+    None,
+    PKCS7,
+    Zeros,
+    ANSIX923,
+    ISO10126,
+}
+
+/// Settings of a `RijndaelManaged` instance, `encrypt`/`decrypt` behave like
+/// `TransformFinalBlock` of its encryptor/decryptor.
+///
+/// In CFB mode .NET Framework pads to `feedback_size` instead of the block
+/// size, so e.g. PKCS7 with 8 bit feedback always appends a single byte.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RijndaelManaged {
+    pub key: Vec<u8>,
+    pub iv: Vec<u8>,
+    pub block_size: usize,
+    pub feedback_size: usize,
+    pub mode: CipherMode,
+    pub padding: PaddingMode,
+}
+
+impl Default for RijndaelManaged {
+    fn default() -> Self {
+        Self {
+            key: Vec::new(),
+            iv: Vec::new(),
+            block_size: 128,
+            feedback_size: 128,
+            mode: CipherMode::CBC,
+            padding: PaddingMode::PKCS7,
+        }
+    }
+}
+
+impl RijndaelManaged {
+    #[inline(always)]
+    pub fn new(key: &[u8], iv: &[u8]) -> Self {
+        Self {
+            key: key.into(),
+            iv: iv.into(),
+            ..Default::default()
+        }
+    }
+
+    /// `Key = bytes.GetBytes(key_size / 8); IV = bytes.GetBytes(BlockSize / 8);`
+    #[inline(always)]
+    pub fn derive(
+        &mut self,
+        bytes: &mut Rfc2898DeriveBytes,
+        key_size: usize,
+    ) -> Result<(), Errors> {
+        self.key = bytes.get_bytes(key_size / 8)?;
+        self.iv = bytes.get_bytes(self.block_size / 8)?;
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn input_block_size(&self) -> usize {
+        match self.mode {
+            CipherMode::CFB => self.feedback_size / 8,
+            _ => self.block_size / 8,
+        }
+    }
+
+    #[allow(clippy::manual_is_multiple_of)]
+    #[inline(always)]
+    fn rijndael(&self) -> Result<Rijndael, Errors> {
+        if self.block_size % 8 != 0 {
+            return Err(Errors::InvalidBlockSize {
+                expected: Size::Rijndael,
+                actual: self.block_size.div_ceil(8),
+            });
+        }
+        if self.feedback_size % 8 != 0
+            || self.feedback_size == 0
+            || self.feedback_size > self.block_size
+        {
+            return Err(Errors::InvalidSegmentSize {
+                expected: Size::Between(1, self.block_size / 8),
+                actual: self.feedback_size.div_ceil(8),
+            });
+        }
+        Rijndael::new(&self.key, self.block_size / 8)
+    }
+
+    #[allow(clippy::manual_is_multiple_of)]
+    pub fn encrypt(&self, data: &[u8]) -> Result<Vec<u8>, Errors> {
+        let rijndael = self.rijndael()?;
+        let size = self.input_block_size();
+        let data = data.to_vec();
+        let padded = match self.padding {
+            PaddingMode::None if data.len() % size != 0 => {
+                return Err(Errors::InvalidDataSize {
+                    expected: Size::MultipleOf(size),
+                    actual: data.len(),
+                })
+            }
+            PaddingMode::None => data,
+            PaddingMode::PKCS7 => Pkcs7Padding::new(size)?.encode(data),
+            PaddingMode::Zeros => ZeroPadding::new(size)?.encode(data),
+            PaddingMode::ANSIX923 => AnsiX923Padding::new(size)?.encode(data),
+            PaddingMode::ISO10126 => Iso10126Padding::new(size)?.encode(data),
+        };
+        match self.mode {
+            CipherMode::ECB => modes::ecb_encrypt(&rijndael, &padded),
+            CipherMode::CBC => modes::cbc_encrypt(&rijndael, &self.iv, &padded),
+            CipherMode::CFB => modes::cfb_encrypt(&rijndael, &self.iv, size, &padded),
+        }
+    }
+
+    #[allow(clippy::manual_is_multiple_of)]
+    pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, Errors> {
+        let rijndael = self.rijndael()?;
+        let size = self.input_block_size();
+        if data.len() % size != 0 {
+            return Err(Errors::InvalidDataSize {
+                expected: Size::MultipleOf(size),
+                actual: data.len(),
+            });
+        }
+        let decrypted = match self.mode {
+            CipherMode::ECB => modes::ecb_decrypt(&rijndael, data)?,
+            CipherMode::CBC => modes::cbc_decrypt(&rijndael, &self.iv, data)?,
+            CipherMode::CFB => modes::cfb_decrypt(&rijndael, &self.iv, size, data)?,
+        };
+        match self.padding {
+            // zero padding can't be told apart from data and is left in place
+            PaddingMode::None | PaddingMode::Zeros => Ok(decrypted),
+            PaddingMode::PKCS7 => Pkcs7Padding::new(size)?.decode(decrypted),
+            PaddingMode::ANSIX923 => AnsiX923Padding::new(size)?.decode(decrypted),
+            PaddingMode::ISO10126 => Iso10126Padding::new(size)?.decode(decrypted),
+        }
+    }
+}
+
+/// `System.Security.Cryptography.Rfc2898DeriveBytes` (PBKDF2 with
+/// HMAC-SHA1). Like in .NET, consecutive `get_bytes` calls continue the
+/// derived byte stream, each PBKDF2 block is computed once.
+#[derive(Debug, Clone)]
+pub struct Rfc2898DeriveBytes {
+    password: Vec<u8>,
+    salt: Vec<u8>,
+    iterations: u32,
+    /// Index of the last computed PBKDF2 block, 1 based.
+    block: u32,
+    /// Bytes of that block not returned yet.
+    buffer: Vec<u8>,
+}
+
+impl Rfc2898DeriveBytes {
+    /// .NET rejects salts shorter than 8 bytes and zero iterations.
+    #[inline(always)]
+    pub fn new(password: &[u8], salt: &[u8], iterations: u32) -> Result<Self, Errors> {
+        if salt.len() < 8 || iterations == 0 {
+            return Err(Errors::InvalidKdfParameters);
+        }
+        Ok(Self {
+            password: password.into(),
+            salt: salt.into(),
+            iterations,
+            block: 0,
+            buffer: Vec::new(),
+        })
+    }
+
+    /// PBKDF2's `F(password, salt, iterations, index)`.
+    fn compute_block(&self, index: u32) -> Vec<u8> {
+        let prf =
+            Hmac::<Sha1>::new_from_slice(&self.password).expect("HMAC accepts keys of any length");
+        let mut u = prf
+            .clone()
+            .chain_update(&self.salt)
+            .chain_update(index.to_be_bytes())
+            .finalize()
+            .into_bytes();
+        let mut block = u.to_vec();
+        for _ in 1..self.iterations {
+            u = prf.clone().chain_update(u).finalize().into_bytes();
+            for (b, u) in block.iter_mut().zip(u) {
+                *b ^= u
+            }
+        }
+        block
+    }
+
+    /// Fails like .NET once the stream runs out of block indexes.
+    pub fn get_bytes(&mut self, count: usize) -> Result<Vec<u8>, Errors> {
+        let mut bytes = Vec::with_capacity(count);
+        while bytes.len() < count {
+            if self.buffer.is_empty() {
+                self.block = self
+                    .block
+                    .checked_add(1)
+                    .ok_or(Errors::InvalidKdfParameters)?;
+                self.buffer = self.compute_block(self.block);
+            }
+            let take = self.buffer.len().min(count - bytes.len());
+            bytes.extend(self.buffer.drain(..take));
+        }
+        Ok(bytes)
+    }
+
+    #[inline(always)]
+    pub fn reset(&mut self) {
+        self.block = 0;
+        self.buffer.clear();
+    }
+}
@@ -0,0 +1,481 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::{
+    constants::VALID,
+    kdf::{self, Prf},
+    modes::{self, Mode},
+    paddings::{Padding, PaddingKind},
+    rijndael::Rijndael,
+    Errors, Size,
+};
+
+// Self-describing container for ciphertexts of this crate. All integers are
+// big endian.
+//
+// | size | field                                             |
+// |------|---------------------------------------------------|
+// | 4    | `MAGIC`                                           |
+// | 1    | `VERSION`                                         |
+// | 1    | mode id (`Mode`: 1 ECB, 2 CBC, 3 CFB, 4 OFB)      |
+// | 1    | block size in bytes                               |
+// | 1    | key size in bytes                                 |
+// | 1    | padding id, 0 for none (`PaddingKind`: 1 zero,    |
+// |      | 2 PKCS#7, 3 ANSI X9.23, 4 ISO 10126)              |
+// | 1    | flags, `FLAG_KDF` and `FLAG_MAC`                  |
+// | bs   | IV, absent in ECB                                 |
+// |      | if `FLAG_KDF`: prf id (1), iterations (4),        |
+// |      | salt length (1), salt                             |
+// | 4    | ciphertext length                                 |
+// |      | ciphertext                                        |
+// | 32   | if `FLAG_MAC`: HMAC-SHA256 of all preceding bytes |
+//
+// With `FLAG_KDF` the key passed to `open` is a password and the master key
+// is derived from it with PBKDF2 (at most `MAX_ITERATIONS`), otherwise the key
+// is the master key. The cipher and MAC keys are derived from the master key
+// with HKDF-SHA256 and `ENC_INFO` / `MAC_INFO`, so neither is used directly.
+//
+// `open` and `Envelope::decrypt` require the MAC, envelopes sealed without one
+// can only be read with `open_unauthenticated`.
+
+pub const MAGIC: &[u8; 4] = b"RJNE";
+/// 2 since the cipher key is derived with HKDF, version 1 is rejected.
+pub const VERSION: u8 = 2;
+pub const FLAG_KDF: u8 = 0b01;
+pub const FLAG_MAC: u8 = 0b10;
+pub const MAC_SIZE: usize = 32;
+/// PBKDF2 iterations are read before the tag can be checked, larger counts
+/// are rejected by `parse` and `seal` so a crafted header can't burn CPU.
+pub const MAX_ITERATIONS: u32 = 2_000_000;
+const ENC_INFO: &[u8] = b"simple-rijndael envelope enc";
+const MAC_INFO: &[u8] = b"simple-rijndael envelope mac";
+
+impl Mode {
+    #[inline(always)]
+    fn envelope_id(&self) -> u8 {
+        match self {
+            Mode::Ecb => 1,
+            Mode::Cbc => 2,
+            Mode::Cfb => 3,
+            Mode::Ofb => 4,
+        }
+    }
+
+    #[inline(always)]
+    fn from_envelope_id(id: u8) -> Result<Self, Errors> {
+        match id {
+            1 => Ok(Mode::Ecb),
+            2 => Ok(Mode::Cbc),
+            3 => Ok(Mode::Cfb),
+            4 => Ok(Mode::Ofb),
+            _ => Err(Errors::InvalidMode),
+        }
+    }
+}
+
+impl PaddingKind {
+    /// 0 is no padding.
+    #[inline(always)]
+    fn envelope_id(padding: Option<&Self>) -> u8 {
+        match padding {
+            None => 0,
+            Some(PaddingKind::Zero(_)) => 1,
+            Some(PaddingKind::Pkcs7(_)) => 2,
+            Some(PaddingKind::AnsiX923(_)) => 3,
+            Some(PaddingKind::Iso10126(_)) => 4,
+        }
+    }
+
+    #[inline(always)]
+    fn from_envelope_id(id: u8, block_size: usize) -> Result<Option<Self>, Errors> {
+        let name = match id {
+            0 => return Ok(None),
+            1 => "zero",
+            2 => "pkcs7",
+            3 => "ansix923",
+            4 => "iso10126",
+            _ => return Err(Errors::InvalidPadding),
+        };
+        PaddingKind::from_name(name, block_size).map(Some)
+    }
+}
+
+/// PBKDF2 parameters stored in the envelope.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KdfParams {
+    pub prf: Prf,
+    pub iterations: u32,
+    pub salt: Vec<u8>,
+}
+
+impl KdfParams {
+    #[inline(always)]
+    fn prf_id(&self) -> u8 {
+        match self.prf {
+            Prf::HmacSha1 => 1,
+            Prf::HmacSha256 => 2,
+        }
+    }
+
+    #[inline(always)]
+    fn prf_from_id(id: u8) -> Result<Prf, Errors> {
+        match id {
+            1 => Ok(Prf::HmacSha1),
+            2 => Ok(Prf::HmacSha256),
+            _ => Err(Errors::InvalidKdfParameters),
+        }
+    }
+}
+
+/// How `seal` encrypts. `key_size` is the size of the cipher key, with `kdf`
+/// it is the size derived from the password. CFB and OFB feed back whole
+/// blocks and take no padding, which has to be built for `block_size`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Params {
+    pub mode: Mode,
+    pub block_size: usize,
+    pub key_size: usize,
+    pub padding: Option<PaddingKind>,
+    pub kdf: Option<KdfParams>,
+    pub mac: bool,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            mode: Mode::Cbc,
+            block_size: 16,
+            key_size: 32,
+            padding: Some(PaddingKind::new(16).expect("16 is a valid block size")),
+            kdf: None,
+            mac: true,
+        }
+    }
+}
+
+/// A parsed envelope.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Envelope {
+    pub params: Params,
+    pub iv: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+    pub tag: Option<[u8; MAC_SIZE]>,
+}
+
+/// Reads the fields in order and fails on truncated input.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    #[inline(always)]
+    fn take(&mut self, size: usize) -> Result<&'a [u8], Errors> {
+        if self.0.len() < size {
+            return Err(Errors::InvalidHeader);
+        }
+        let (head, tail) = self.0.split_at(size);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    #[inline(always)]
+    fn u8(&mut self) -> Result<u8, Errors> {
+        Ok(self.take(1)?[0])
+    }
+
+    #[inline(always)]
+    fn u32(&mut self) -> Result<u32, Errors> {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(self.take(4)?);
+        Ok(u32::from_be_bytes(bytes))
+    }
+}
+
+impl Envelope {
+    /// Parses an envelope without decrypting it. Unknown versions, ids and
+    /// flags, invalid sizes, truncated input and trailing bytes are errors.
+    pub fn parse(data: &[u8]) -> Result<Self, Errors> {
+        let mut reader = Reader(data);
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(Errors::InvalidHeader);
+        }
+        if reader.u8()? != VERSION {
+            return Err(Errors::InvalidVersion);
+        }
+        let mode = Mode::from_envelope_id(reader.u8()?)?;
+        let block_size = reader.u8()? as usize;
+        if !VALID.contains(&block_size) {
+            return Err(Errors::InvalidBlockSize {
+                expected: Size::Rijndael,
+                actual: block_size,
+            });
+        }
+        let key_size = reader.u8()? as usize;
+        if !VALID.contains(&key_size) {
+            return Err(Errors::InvalidKeySize {
+                expected: Size::Rijndael,
+                actual: key_size,
+            });
+        }
+        let padding = PaddingKind::from_envelope_id(reader.u8()?, block_size)?;
+        if !mode.is_block_mode() && padding.is_some() {
+            return Err(Errors::InvalidPadding);
+        }
+        let flags = reader.u8()?;
+        if flags & !(FLAG_KDF | FLAG_MAC) != 0 {
+            return Err(Errors::InvalidHeader);
+        }
+        let iv = match mode {
+            Mode::Ecb => Vec::new(),
+            _ => reader.take(block_size)?.to_vec(),
+        };
+        let kdf = match flags & FLAG_KDF {
+            0 => None,
+            _ => {
+                let prf = KdfParams::prf_from_id(reader.u8()?)?;
+                let iterations = reader.u32()?;
+                let salt_size = reader.u8()? as usize;
+                let salt = reader.take(salt_size)?.to_vec();
+                if iterations == 0 || iterations > MAX_ITERATIONS {
+                    return Err(Errors::InvalidKdfParameters);
+                }
+                Some(KdfParams {
+                    prf,
+                    iterations,
+                    salt,
+                })
+            }
+        };
+        let ciphertext_size = reader.u32()? as usize;
+        let ciphertext = reader.take(ciphertext_size)?.to_vec();
+        if mode.is_block_mode() && ciphertext.len() % block_size != 0 {
+            return Err(Errors::InvalidDataSize {
+                expected: Size::MultipleOf(block_size),
+                actual: ciphertext.len(),
+            });
+        }
+        let tag = match flags & FLAG_MAC {
+            0 => None,
+            _ => {
+                let mut tag = [0; MAC_SIZE];
+                tag.copy_from_slice(reader.take(MAC_SIZE)?);
+                Some(tag)
+            }
+        };
+        if !reader.0.is_empty() {
+            return Err(Errors::InvalidDataSize {
+                expected: Size::Exactly(data.len() - reader.0.len()),
+                actual: data.len(),
+            });
+        }
+        Ok(Self {
+            params: Params {
+                mode,
+                block_size,
+                key_size,
+                padding,
+                kdf,
+                mac: tag.is_some(),
+            },
+            iv,
+            ciphertext,
+            tag,
+        })
+    }
+
+    /// Serializes the header and ciphertext, without the MAC tag.
+    fn body(&self) -> Vec<u8> {
+        let params = &self.params;
+        let mut flags = 0;
+        if params.kdf.is_some() {
+            flags |= FLAG_KDF;
+        }
+        if params.mac {
+            flags |= FLAG_MAC;
+        }
+        let mut result = Vec::with_capacity(64 + self.iv.len() + self.ciphertext.len());
+        result.extend_from_slice(MAGIC);
+        result.extend([
+            VERSION,
+            params.mode.envelope_id(),
+            params.block_size as u8,
+            params.key_size as u8,
+            PaddingKind::envelope_id(params.padding.as_ref()),
+            flags,
+        ]);
+        result.extend_from_slice(&self.iv);
+        if let Some(kdf) = &params.kdf {
+            result.push(kdf.prf_id());
+            result.extend(kdf.iterations.to_be_bytes());
+            result.push(kdf.salt.len() as u8);
+            result.extend_from_slice(&kdf.salt);
+        }
+        result.extend((self.ciphertext.len() as u32).to_be_bytes());
+        result.extend_from_slice(&self.ciphertext);
+        result
+    }
+
+    #[inline(always)]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut result = self.body();
+        if let Some(tag) = &self.tag {
+            result.extend_from_slice(tag);
+        }
+        result
+    }
+
+    /// The master key, derived from `key` if the envelope has KDF parameters.
+    #[inline(always)]
+    fn master_key(&self, key: &[u8]) -> Result<Vec<u8>, Errors> {
+        match &self.params.kdf {
+            Some(kdf) => kdf::pbkdf2(
+                kdf.prf,
+                key,
+                &kdf.salt,
+                kdf.iterations,
+                self.params.key_size,
+            ),
+            None if key.len() == self.params.key_size => Ok(key.to_vec()),
+            None => Err(Errors::InvalidKeySize {
+                expected: Size::Exactly(self.params.key_size),
+                actual: key.len(),
+            }),
+        }
+    }
+
+    #[inline(always)]
+    fn cipher(&self, master_key: &[u8]) -> Result<Rijndael, Errors> {
+        let key = kdf::hkdf_sha256(master_key, None, ENC_INFO, self.params.key_size)?;
+        Rijndael::new(&key, self.params.block_size)
+    }
+
+    #[inline(always)]
+    fn mac(&self, master_key: &[u8]) -> Result<Hmac<Sha256>, Errors> {
+        let mac_key = kdf::hkdf_sha256(master_key, None, MAC_INFO, MAC_SIZE)?;
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&mac_key).expect("HMAC accepts keys of any length");
+        mac.update(&self.body());
+        Ok(mac)
+    }
+
+    /// Verifies the tag and decrypts, envelopes without a tag are rejected
+    /// with `AuthenticationFailed`. The tag is checked before any
+    /// decryption, in constant time.
+    pub fn decrypt(&self, key: &[u8]) -> Result<Vec<u8>, Errors> {
+        if self.tag.is_none() {
+            return Err(Errors::AuthenticationFailed);
+        }
+        self.decrypt_unauthenticated(key)
+    }
+
+    /// Like `decrypt`, but also accepts envelopes sealed without a MAC. Only
+    /// for envelopes whose integrity is ensured otherwise.
+    pub fn decrypt_unauthenticated(&self, key: &[u8]) -> Result<Vec<u8>, Errors> {
+        let params = &self.params;
+        let master_key = self.master_key(key)?;
+        if let Some(tag) = &self.tag {
+            self.mac(&master_key)?
+                .verify_slice(tag)
+                .map_err(|_| Errors::AuthenticationFailed)?;
+        }
+        let rijndael = self.cipher(&master_key)?;
+        let decrypted = match params.mode {
+            Mode::Ecb => modes::ecb_decrypt(&rijndael, &self.ciphertext)?,
+            Mode::Cbc => modes::cbc_decrypt(&rijndael, &self.iv, &self.ciphertext)?,
+            Mode::Cfb => {
+                modes::cfb_decrypt(&rijndael, &self.iv, params.block_size, &self.ciphertext)?
+            }
+            Mode::Ofb => modes::ofb(&rijndael, &self.iv, params.block_size, &self.ciphertext)?,
+        };
+        match &params.padding {
+            Some(padding) => padding.decode(decrypted),
+            None => Ok(decrypted),
+        }
+    }
+}
+
+/// Encrypts `data` into an envelope. `key` is the cipher key, or the password
+/// if `params.kdf` is set. `iv` must be empty in ECB and one block otherwise.
+pub fn seal(params: &Params, key: &[u8], iv: &[u8], data: &[u8]) -> Result<Vec<u8>, Errors> {
+    if !VALID.contains(&params.block_size) {
+        return Err(Errors::InvalidBlockSize {
+            expected: Size::Rijndael,
+            actual: params.block_size,
+        });
+    }
+    if !VALID.contains(&params.key_size) {
+        return Err(Errors::InvalidKeySize {
+            expected: Size::Rijndael,
+            actual: params.key_size,
+        });
+    }
+    let iv_size = match params.mode {
+        Mode::Ecb => 0,
+        _ => params.block_size,
+    };
+    if iv.len() != iv_size {
+        return Err(Errors::InvalidIvSize {
+            expected: Size::Exactly(iv_size),
+            actual: iv.len(),
+        });
+    }
+    if let Some(padding) = &params.padding {
+        if !params.mode.is_block_mode() {
+            return Err(Errors::InvalidPadding);
+        }
+        if padding.size() != params.block_size {
+            return Err(Errors::InvalidBlockSize {
+                expected: Size::Exactly(params.block_size),
+                actual: padding.size(),
+            });
+        }
+    }
+    if let Some(kdf) = &params.kdf {
+        if kdf.iterations == 0
+            || kdf.iterations > MAX_ITERATIONS
+            || kdf.salt.len() > u8::MAX as usize
+        {
+            return Err(Errors::InvalidKdfParameters);
+        }
+    }
+    let mut envelope = Envelope {
+        params: params.clone(),
+        iv: iv.to_vec(),
+        ciphertext: Vec::new(),
+        tag: None,
+    };
+    let master_key = envelope.master_key(key)?;
+    let rijndael = envelope.cipher(&master_key)?;
+    let padded = match &params.padding {
+        Some(padding) => padding.encode(data.to_vec()),
+        None => data.to_vec(),
+    };
+    envelope.ciphertext = match params.mode {
+        Mode::Ecb => modes::ecb_encrypt(&rijndael, &padded)?,
+        Mode::Cbc => modes::cbc_encrypt(&rijndael, iv, &padded)?,
+        Mode::Cfb => modes::cfb_encrypt(&rijndael, iv, params.block_size, &padded)?,
+        Mode::Ofb => modes::ofb(&rijndael, iv, params.block_size, &padded)?,
+    };
+    if u32::try_from(envelope.ciphertext.len()).is_err() {
+        return Err(Errors::InvalidDataSize {
+            expected: Size::Between(0, u32::MAX as usize),
+            actual: envelope.ciphertext.len(),
+        });
+    }
+    if params.mac {
+        envelope.tag = Some(envelope.mac(&master_key)?.finalize().into_bytes().into());
+    }
+    Ok(envelope.to_bytes())
+}
+
+/// Parses, authenticates and decrypts an envelope produced by `seal`.
+/// Envelopes without a MAC fail with `AuthenticationFailed`.
+#[inline(always)]
+pub fn open(key: &[u8], data: &[u8]) -> Result<Vec<u8>, Errors> {
+    Envelope::parse(data)?.decrypt(key)
+}
+
+/// Like `open`, but also accepts envelopes sealed with `mac: false`, see
+/// `Envelope::decrypt_unauthenticated`.
+#[inline(always)]
+pub fn open_unauthenticated(key: &[u8], data: &[u8]) -> Result<Vec<u8>, Errors> {
+    Envelope::parse(data)?.decrypt_unauthenticated(key)
+}
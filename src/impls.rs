@@ -1,4 +1,6 @@
-use crate::{paddings::Padding, rijndael::Rijndael, Errors};
+use std::sync::Arc;
+
+use crate::{paddings::Padding, rijndael::Rijndael, types::IntoIv, Errors, Size};
 
 #[derive(Debug)]
 pub struct RijndaelCbc<P: Padding> {
@@ -14,61 +16,276 @@ where
     pub fn new(key: &[u8], block_size: usize) -> Result<Self, Errors> {
         Ok(Self {
             rijndael: Rijndael::new(key, block_size)?,
-            padding: P::new(block_size),
+            padding: P::new(block_size)?,
+        })
+    }
+
+    /// Uses an already built padding, e.g. a `PaddingKind` chosen at runtime.
+    /// The block size is `padding.size()`.
+    #[inline(always)]
+    pub fn with_padding(key: &[u8], padding: P) -> Result<Self, Errors> {
+        Ok(Self {
+            rijndael: Rijndael::new(key, padding.size())?,
+            padding,
         })
     }
 
+    #[allow(clippy::manual_is_multiple_of)]
     #[inline(always)]
-    pub fn encrypt(&self, iv: &[u8], source: Vec<u8>) -> Result<Vec<u8>, Errors> {
+    fn check_data_size(&self, length: usize) -> Result<(), Errors> {
+        let block_size = self.rijndael.block_size();
+        if length % block_size != 0 {
+            return Err(Errors::InvalidDataSize {
+                expected: Size::MultipleOf(block_size),
+                actual: length,
+            });
+        }
+        Ok(())
+    }
+
+    /// Empty input gives empty output if the padding adds nothing to it
+    /// (`ZeroPadding`).
+    #[inline(always)]
+    pub fn encrypt<'a>(&self, iv: impl IntoIv<'a>, source: Vec<u8>) -> Result<Vec<u8>, Errors> {
+        let iv = iv.into_iv(self.rijndael.block_size())?;
         let ppt = self.padding.encode(source);
         let length = ppt.len();
+        self.check_data_size(length)?;
         let mut offset = 0;
         let mut ct = Vec::with_capacity(length);
-        let mut v = iv.into();
-        let mut block;
-        loop {
-            block = self.rijndael.encrypt(
+        let mut v = iv.to_vec();
+        while offset < length {
+            let block = self.rijndael.encrypt(
                 &self.x_or_block(v, &ppt[offset..(offset + self.rijndael.block_size())]),
             )?;
             ct.extend(&block);
             offset += self.rijndael.block_size();
-
-            if offset >= length {
-                break;
-            }
             v = block;
         }
         Ok(ct)
     }
 
+    /// Empty input is passed to the padding as is, which rejects it unless it
+    /// is `ZeroPadding`.
     #[inline(always)]
-    pub fn decrypt(&self, iv: &[u8], cipher: Vec<u8>) -> Result<Vec<u8>, Errors> {
+    pub fn decrypt<'a>(&self, iv: impl IntoIv<'a>, cipher: Vec<u8>) -> Result<Vec<u8>, Errors> {
+        let iv = iv.into_iv(self.rijndael.block_size())?;
         let length = cipher.len();
-        if (length % self.rijndael.block_size()) != 0 {
-            return Err(Errors::InvalidDataSize);
-        }
+        self.check_data_size(length)?;
         let mut ppt = Vec::with_capacity(length);
         let mut offset = 0;
-        let mut v = iv;
-        loop {
+        let mut v: &[u8] = &iv;
+        while offset < length {
             let block = &cipher[offset..(offset + self.rijndael.block_size())];
-            let decrypted = self.rijndael.decrypt(&block)?;
+            let decrypted = self.rijndael.decrypt(block)?;
             ppt.append(&mut self.x_or_block(decrypted, v));
             offset += self.rijndael.block_size();
-
-            if offset >= length {
-                break;
-            }
             v = block;
         }
-        Ok(self.padding.decode(ppt)?)
+        self.padding.decode(ppt)
     }
 
     #[inline(always)]
     pub fn x_or_block(&self, mut b1: Vec<u8>, b2: &[u8]) -> Vec<u8> {
-        for i in 0..self.rijndael.block_size() {
-            b1[i] ^= b2[i]
+        for (a, b) in b1.iter_mut().zip(b2).take(self.rijndael.block_size()) {
+            *a ^= b
         }
         b1
     }
 }
+
+impl<P> RijndaelCbc<P>
+where
+    P: Padding,
+{
+    /// The IV is checked here, not by `update` and `finalize`. The state
+    /// holds a clone of the `Arc`, so it can be moved into another task.
+    #[inline(always)]
+    pub fn encryptor<'a>(self: &Arc<Self>, iv: impl IntoIv<'a>) -> Result<CbcEncryptor<P>, Errors> {
+        Ok(CbcEncryptor {
+            cbc: Arc::clone(self),
+            chain: iv.into_iv(self.rijndael.block_size())?.to_vec(),
+            buffer: Vec::with_capacity(self.rijndael.block_size()),
+        })
+    }
+
+    /// The IV is checked here, not by `update` and `finalize`. The state
+    /// holds a clone of the `Arc`, so it can be moved into another task.
+    #[inline(always)]
+    pub fn decryptor<'a>(self: &Arc<Self>, iv: impl IntoIv<'a>) -> Result<CbcDecryptor<P>, Errors> {
+        Ok(CbcDecryptor {
+            cbc: Arc::clone(self),
+            chain: iv.into_iv(self.rijndael.block_size())?.to_vec(),
+            buffer: Vec::with_capacity(self.rijndael.block_size()),
+        })
+    }
+
+    /// Encrypts with an IV drawn from `rng` and returns IV || ciphertext.
+    #[cfg(feature = "rand")]
+    #[inline(always)]
+    pub fn encrypt_with_random_iv(
+        &self,
+        rng: &mut impl rand_core::CryptoRngCore,
+        source: Vec<u8>,
+    ) -> Result<Vec<u8>, Errors> {
+        let mut result = vec![0; self.rijndael.block_size()];
+        rng.fill_bytes(&mut result);
+        let ct = self.encrypt(&result, source)?;
+        result.extend(ct);
+        Ok(result)
+    }
+
+    /// Decrypts IV || ciphertext, as produced by `encrypt_with_random_iv`.
+    #[inline(always)]
+    pub fn decrypt_prefixed_iv(&self, data: &[u8]) -> Result<Vec<u8>, Errors> {
+        let block_size = self.rijndael.block_size();
+        if data.len() < block_size {
+            return Err(Errors::InvalidDataSize {
+                expected: Size::AtLeast(block_size),
+                actual: data.len(),
+            });
+        }
+        let (iv, cipher) = data.split_at(block_size);
+        self.decrypt(iv, cipher.to_vec())
+    }
+}
+
+/// Common interface of the incremental encryption and decryption states, used
+/// by the stream adapters.
+pub trait IncrementalCipher {
+    fn update(&mut self, input: &[u8], out: &mut Vec<u8>) -> Result<(), Errors>;
+    fn finalize(self, out: &mut Vec<u8>) -> Result<(), Errors>;
+}
+
+/// Incremental CBC encryption state.
+///
+/// Holds the chaining value and the bytes of a not yet complete block, so
+/// input can be fed in arbitrary pieces. Padding is applied by `finalize`.
+#[derive(Debug)]
+pub struct CbcEncryptor<P: Padding> {
+    cbc: Arc<RijndaelCbc<P>>,
+    chain: Vec<u8>,
+    buffer: Vec<u8>,
+}
+
+impl<P> CbcEncryptor<P>
+where
+    P: Padding,
+{
+    #[inline(always)]
+    pub fn update(&mut self, input: &[u8], out: &mut Vec<u8>) -> Result<(), Errors> {
+        let block_size = self.cbc.rijndael.block_size();
+        self.buffer.extend_from_slice(input);
+        let mut offset = 0;
+        while self.buffer.len() - offset >= block_size {
+            let block = self.cbc.rijndael.encrypt(&self.cbc.x_or_block(
+                std::mem::take(&mut self.chain),
+                &self.buffer[offset..(offset + block_size)],
+            ))?;
+            out.extend(&block);
+            self.chain = block;
+            offset += block_size;
+        }
+        self.buffer.drain(..offset);
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn finalize(mut self, out: &mut Vec<u8>) -> Result<(), Errors> {
+        let rest = self.cbc.padding.encode(std::mem::take(&mut self.buffer));
+        self.update(&rest, out)?;
+        if !self.buffer.is_empty() {
+            return Err(Errors::InvalidDataSize {
+                expected: Size::MultipleOf(self.cbc.rijndael.block_size()),
+                actual: rest.len(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Incremental CBC decryption state.
+///
+/// The last complete block is held back until `finalize`, where the padding
+/// is removed from it.
+#[derive(Debug)]
+pub struct CbcDecryptor<P: Padding> {
+    cbc: Arc<RijndaelCbc<P>>,
+    chain: Vec<u8>,
+    buffer: Vec<u8>,
+}
+
+impl<P> CbcDecryptor<P>
+where
+    P: Padding,
+{
+    #[inline(always)]
+    pub fn update(&mut self, input: &[u8], out: &mut Vec<u8>) -> Result<(), Errors> {
+        let block_size = self.cbc.rijndael.block_size();
+        self.buffer.extend_from_slice(input);
+        let mut offset = 0;
+        while self.buffer.len() - offset > block_size {
+            let block = &self.buffer[offset..(offset + block_size)];
+            let decrypted = self.cbc.rijndael.decrypt(block)?;
+            out.append(&mut self.cbc.x_or_block(decrypted, &self.chain));
+            self.chain.copy_from_slice(block);
+            offset += block_size;
+        }
+        self.buffer.drain(..offset);
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn finalize(self, out: &mut Vec<u8>) -> Result<(), Errors> {
+        let block_size = self.cbc.rijndael.block_size();
+        // no input at all, like `RijndaelCbc::decrypt` of an empty vec
+        if self.buffer.is_empty() {
+            out.append(&mut self.cbc.padding.decode(Vec::new())?);
+            return Ok(());
+        }
+        if self.buffer.len() != block_size {
+            return Err(Errors::InvalidDataSize {
+                expected: Size::MultipleOf(block_size),
+                actual: self.buffer.len(),
+            });
+        }
+        let decrypted = self.cbc.rijndael.decrypt(&self.buffer)?;
+        out.append(
+            &mut self
+                .cbc
+                .padding
+                .decode(self.cbc.x_or_block(decrypted, &self.chain))?,
+        );
+        Ok(())
+    }
+}
+
+impl<P> IncrementalCipher for CbcEncryptor<P>
+where
+    P: Padding,
+{
+    #[inline(always)]
+    fn update(&mut self, input: &[u8], out: &mut Vec<u8>) -> Result<(), Errors> {
+        CbcEncryptor::update(self, input, out)
+    }
+
+    #[inline(always)]
+    fn finalize(self, out: &mut Vec<u8>) -> Result<(), Errors> {
+        CbcEncryptor::finalize(self, out)
+    }
+}
+
+impl<P> IncrementalCipher for CbcDecryptor<P>
+where
+    P: Padding,
+{
+    #[inline(always)]
+    fn update(&mut self, input: &[u8], out: &mut Vec<u8>) -> Result<(), Errors> {
+        CbcDecryptor::update(self, input, out)
+    }
+
+    #[inline(always)]
+    fn finalize(self, out: &mut Vec<u8>) -> Result<(), Errors> {
+        CbcDecryptor::finalize(self, out)
+    }
+}
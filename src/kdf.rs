@@ -0,0 +1,130 @@
+use hkdf::Hkdf;
+use sha1::Sha1;
+use sha2::Sha256;
+
+use crate::{constants::VALID, impls::RijndaelCbc, paddings::Padding, Errors, Size};
+
+/// Pseudo random function of PBKDF2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Prf {
+    HmacSha1,
+    HmacSha256,
+}
+
+#[inline(always)]
+pub fn pbkdf2(
+    prf: Prf,
+    password: &[u8],
+    salt: &[u8],
+    iterations: u32,
+    size: usize,
+) -> Result<Vec<u8>, Errors> {
+    if iterations == 0 {
+        return Err(Errors::InvalidKdfParameters);
+    }
+    let mut derived = vec![0; size];
+    match prf {
+        Prf::HmacSha1 => pbkdf2::pbkdf2_hmac::<Sha1>(password, salt, iterations, &mut derived),
+        Prf::HmacSha256 => pbkdf2::pbkdf2_hmac::<Sha256>(password, salt, iterations, &mut derived),
+    }
+    Ok(derived)
+}
+
+/// HKDF-SHA256 (RFC 5869) extract and expand, at most 8160 bytes.
+#[inline(always)]
+pub fn hkdf_sha256(
+    ikm: &[u8],
+    salt: Option<&[u8]>,
+    info: &[u8],
+    size: usize,
+) -> Result<Vec<u8>, Errors> {
+    let mut derived = vec![0; size];
+    Hkdf::<Sha256>::new(salt, ikm)
+        .expand(info, &mut derived)
+        .map_err(|_| Errors::InvalidKdfParameters)?;
+    Ok(derived)
+}
+
+/// Key and IV for a cipher with the given block and key size, taken from the
+/// start of the derived bytes (key first).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyMaterial {
+    pub key: Vec<u8>,
+    pub iv: Vec<u8>,
+}
+
+impl KeyMaterial {
+    #[inline(always)]
+    fn split(mut derived: Vec<u8>, key_size: usize) -> Self {
+        let iv = derived.split_off(key_size);
+        Self { key: derived, iv }
+    }
+
+    #[inline(always)]
+    fn check_sizes(block_size: usize, key_size: usize) -> Result<(), Errors> {
+        if !VALID.contains(&block_size) {
+            return Err(Errors::InvalidBlockSize {
+                expected: Size::Rijndael,
+                actual: block_size,
+            });
+        }
+        if !VALID.contains(&key_size) {
+            return Err(Errors::InvalidKeySize {
+                expected: Size::Rijndael,
+                actual: key_size,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn pbkdf2(
+        prf: Prf,
+        password: &[u8],
+        salt: &[u8],
+        iterations: u32,
+        block_size: usize,
+        key_size: usize,
+    ) -> Result<Self, Errors> {
+        Self::check_sizes(block_size, key_size)?;
+        let derived = pbkdf2(prf, password, salt, iterations, key_size + block_size)?;
+        Ok(Self::split(derived, key_size))
+    }
+
+    pub fn hkdf_sha256(
+        ikm: &[u8],
+        salt: Option<&[u8]>,
+        info: &[u8],
+        block_size: usize,
+        key_size: usize,
+    ) -> Result<Self, Errors> {
+        Self::check_sizes(block_size, key_size)?;
+        let derived = hkdf_sha256(ikm, salt, info, key_size + block_size)?;
+        Ok(Self::split(derived, key_size))
+    }
+}
+
+impl<P> RijndaelCbc<P>
+where
+    P: Padding,
+{
+    /// Derives key and IV with PBKDF2-HMAC-SHA256, returns the cipher and the
+    /// IV to use with it.
+    #[inline(always)]
+    pub fn from_password(
+        password: &[u8],
+        salt: &[u8],
+        iterations: u32,
+        block_size: usize,
+        key_size: usize,
+    ) -> Result<(Self, Vec<u8>), Errors> {
+        let material = KeyMaterial::pbkdf2(
+            Prf::HmacSha256,
+            password,
+            salt,
+            iterations,
+            block_size,
+            key_size,
+        )?;
+        Ok((Self::new(&material.key, block_size)?, material.iv))
+    }
+}
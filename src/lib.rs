@@ -1,16 +1,101 @@
 #[cfg(test)]
 pub mod tests;
 
+#[cfg(feature = "tokio")]
+pub mod async_io;
+#[cfg(feature = "capi")]
+pub mod capi;
+#[cfg(feature = "hmac")]
+pub mod cbc_hmac;
+pub mod cipher;
+#[cfg(feature = "serde")]
+pub mod config;
 pub mod constants;
+#[cfg(feature = "dotnet")]
+pub mod dotnet;
+#[cfg(feature = "envelope")]
+pub mod envelope;
 pub mod impls;
+#[cfg(feature = "kdf")]
+pub mod kdf;
+pub mod mcrypt;
+pub mod modes;
+#[cfg(feature = "openssl")]
+pub mod openssl;
+#[cfg(feature = "osu")]
+pub mod osu;
 pub mod paddings;
 pub mod rijndael;
+mod tables;
+pub mod types;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
-#[derive(Debug)]
+/// The size an argument should have had, carried by the `Invalid*Size`
+/// variants of `Errors`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Size {
+    Exactly(usize),
+    MultipleOf(usize),
+    AtLeast(usize),
+    /// Inclusive range.
+    Between(usize, usize),
+    /// A Rijndael block or key size, 16, 24 or 32 bytes.
+    Rijndael,
+}
+
+#[cfg(feature = "std")]
+impl std::fmt::Display for Size {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Size::Exactly(size) => write!(f, "{} bytes", size),
+            Size::MultipleOf(size) => write!(f, "a multiple of {} bytes", size),
+            Size::AtLeast(size) => write!(f, "at least {} bytes", size),
+            Size::Between(min, max) => write!(f, "{} to {} bytes", min, max),
+            Size::Rijndael => write!(f, "16, 24 or 32 bytes"),
+        }
+    }
+}
+
+/// The variant names which argument was wrong, sizes are in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum Errors {
-    InvalidDataSize,
-    InvalidBlockSize,
-    InvalidKeySize,
+    InvalidDataSize {
+        expected: Size,
+        actual: usize,
+    },
+    InvalidBlockSize {
+        expected: Size,
+        actual: usize,
+    },
+    InvalidKeySize {
+        expected: Size,
+        actual: usize,
+    },
+    InvalidIvSize {
+        expected: Size,
+        actual: usize,
+    },
+    InvalidTagSize {
+        expected: Size,
+        actual: usize,
+    },
+    /// CFB/OFB feedback size.
+    InvalidSegmentSize {
+        expected: Size,
+        actual: usize,
+    },
+    InvalidAlgorithm,
+    InvalidBase64,
+    InvalidScoreData,
+    InvalidKeyFile,
+    InvalidMode,
+    InvalidPadding,
+    InvalidHeader,
+    InvalidKdfParameters,
+    InvalidVersion,
+    AuthenticationFailed,
 }
 
 #[cfg(feature = "std")]
@@ -19,10 +104,28 @@ impl std::error::Error for Errors {}
 #[cfg(feature = "std")]
 impl std::fmt::Display for Errors {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        match self {
-            Errors::InvalidDataSize => write!(f, "Invalid Data Size"),
-            Errors::InvalidBlockSize => write!(f, "Invalid Block Size"),
-            Errors::InvalidKeySize => write!(f, "Invalid Key Size"),
-        }
+        let (name, expected, actual) = match self {
+            Errors::InvalidDataSize { expected, actual } => ("Data", expected, actual),
+            Errors::InvalidBlockSize { expected, actual } => ("Block", expected, actual),
+            Errors::InvalidKeySize { expected, actual } => ("Key", expected, actual),
+            Errors::InvalidIvSize { expected, actual } => ("IV", expected, actual),
+            Errors::InvalidTagSize { expected, actual } => ("Tag", expected, actual),
+            Errors::InvalidSegmentSize { expected, actual } => ("Segment", expected, actual),
+            Errors::InvalidAlgorithm => return write!(f, "Invalid Algorithm"),
+            Errors::InvalidBase64 => return write!(f, "Invalid Base64"),
+            Errors::InvalidScoreData => return write!(f, "Invalid Score Data"),
+            Errors::InvalidKeyFile => return write!(f, "Invalid Key File"),
+            Errors::InvalidMode => return write!(f, "Invalid Mode"),
+            Errors::InvalidPadding => return write!(f, "Invalid Padding"),
+            Errors::InvalidHeader => return write!(f, "Invalid Header"),
+            Errors::InvalidKdfParameters => return write!(f, "Invalid KDF Parameters"),
+            Errors::InvalidVersion => return write!(f, "Invalid Version"),
+            Errors::AuthenticationFailed => return write!(f, "Authentication Failed"),
+        };
+        write!(
+            f,
+            "Invalid {} Size: expected {}, got {}",
+            name, expected, actual
+        )
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,158 @@
+use std::str::FromStr;
+
+use crate::{constants::VALID, modes, rijndael::Rijndael, Errors, Size};
+
+// Compatibility with PHP's `mcrypt_encrypt`/`mcrypt_decrypt` (libmcrypt) for
+// the `MCRYPT_RIJNDAEL_*` ciphers, including its lenient handling of keys and
+// data:
+//
+// - keys are zero padded to the next valid key size, longer keys are cut at 32
+//   (PHP before 5.6)
+// - IVs must be one block (`InvalidIvSize`) like in PHP 5.6 and later, which
+//   return false. PHP before 5.6 warned and used a zero IV, that is not
+//   reproduced. ECB ignores the IV
+// - in ECB and CBC the data (ciphertext too) is zero padded to the block size,
+//   empty data to one block, and decryption leaves the padding in place
+
+/// `MCRYPT_RIJNDAEL_128`, `MCRYPT_RIJNDAEL_192`, `MCRYPT_RIJNDAEL_256`.
+/// The number is the block size, not the key size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    Rijndael128,
+    Rijndael192,
+    Rijndael256,
+}
+
+impl Algorithm {
+    #[inline(always)]
+    pub fn block_size(&self) -> usize {
+        match self {
+            Algorithm::Rijndael128 => 16,
+            Algorithm::Rijndael192 => 24,
+            Algorithm::Rijndael256 => 32,
+        }
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = Errors;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rijndael-128" => Ok(Algorithm::Rijndael128),
+            "rijndael-192" => Ok(Algorithm::Rijndael192),
+            "rijndael-256" => Ok(Algorithm::Rijndael256),
+            _ => Err(Errors::InvalidAlgorithm),
+        }
+    }
+}
+
+/// `MCRYPT_MODE_*`. `Cfb` and `Ofb` use 8 bit feedback, `Ncfb` and `Nofb`
+/// feed back a whole block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Ecb,
+    Cbc,
+    Cfb,
+    Ofb,
+    Ncfb,
+    Nofb,
+}
+
+impl FromStr for Mode {
+    type Err = Errors;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ecb" => Ok(Mode::Ecb),
+            "cbc" => Ok(Mode::Cbc),
+            "cfb" => Ok(Mode::Cfb),
+            "ofb" => Ok(Mode::Ofb),
+            "ncfb" => Ok(Mode::Ncfb),
+            "nofb" => Ok(Mode::Nofb),
+            _ => Err(Errors::InvalidMode),
+        }
+    }
+}
+
+#[inline(always)]
+pub fn normalize_key(key: &[u8]) -> Vec<u8> {
+    let size = VALID
+        .iter()
+        .copied()
+        .find(|size| *size >= key.len())
+        .unwrap_or(VALID[VALID.len() - 1]);
+    let mut key = key[..key.len().min(size)].to_vec();
+    key.resize(size, 0);
+    key
+}
+
+#[inline(always)]
+pub fn check_iv(iv: &[u8], block_size: usize) -> Result<(), Errors> {
+    if iv.len() != block_size {
+        return Err(Errors::InvalidIvSize {
+            expected: Size::Exactly(block_size),
+            actual: iv.len(),
+        });
+    }
+    Ok(())
+}
+
+#[inline(always)]
+fn zero_pad(data: &[u8], block_size: usize) -> Vec<u8> {
+    let mut data = data.to_vec();
+    data.resize(data.len().div_ceil(block_size).max(1) * block_size, 0);
+    data
+}
+
+#[inline(always)]
+fn crypt(
+    algorithm: Algorithm,
+    key: &[u8],
+    data: &[u8],
+    mode: Mode,
+    iv: &[u8],
+    decrypt: bool,
+) -> Result<Vec<u8>, Errors> {
+    let block_size = algorithm.block_size();
+    let rijndael = Rijndael::new(&normalize_key(key), block_size)?;
+    if mode != Mode::Ecb {
+        check_iv(iv, block_size)?;
+    }
+    match (mode, decrypt) {
+        (Mode::Ecb, false) => modes::ecb_encrypt(&rijndael, &zero_pad(data, block_size)),
+        (Mode::Ecb, true) => modes::ecb_decrypt(&rijndael, &zero_pad(data, block_size)),
+        (Mode::Cbc, false) => modes::cbc_encrypt(&rijndael, iv, &zero_pad(data, block_size)),
+        (Mode::Cbc, true) => modes::cbc_decrypt(&rijndael, iv, &zero_pad(data, block_size)),
+        (Mode::Cfb, false) => modes::cfb_encrypt(&rijndael, iv, 1, data),
+        (Mode::Cfb, true) => modes::cfb_decrypt(&rijndael, iv, 1, data),
+        (Mode::Ncfb, false) => modes::cfb_encrypt(&rijndael, iv, block_size, data),
+        (Mode::Ncfb, true) => modes::cfb_decrypt(&rijndael, iv, block_size, data),
+        (Mode::Ofb, _) => modes::ofb(&rijndael, iv, 1, data),
+        (Mode::Nofb, _) => modes::ofb(&rijndael, iv, block_size, data),
+    }
+}
+
+/// Same as `mcrypt_encrypt($algorithm, $key, $data, $mode, $iv)`.
+#[inline(always)]
+pub fn encrypt(
+    algorithm: Algorithm,
+    key: &[u8],
+    data: &[u8],
+    mode: Mode,
+    iv: &[u8],
+) -> Result<Vec<u8>, Errors> {
+    crypt(algorithm, key, data, mode, iv, false)
+}
+
+/// Same as `mcrypt_decrypt($algorithm, $key, $data, $mode, $iv)`.
+#[inline(always)]
+pub fn decrypt(
+    algorithm: Algorithm,
+    key: &[u8],
+    data: &[u8],
+    mode: Mode,
+    iv: &[u8],
+) -> Result<Vec<u8>, Errors> {
+    crypt(algorithm, key, data, mode, iv, true)
+}
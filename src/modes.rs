@@ -0,0 +1,258 @@
+use std::{fmt, str::FromStr};
+
+use crate::{rijndael::Rijndael, Errors, Size};
+
+// Unpadded block cipher modes over `Rijndael`, shared by the compatibility
+// layers. `segment_size` is the feedback size in bytes (1 to block size).
+
+/// The modes of this module by name, `cfb` and `ofb` feeding back whole
+/// blocks. Used by `cipher::Cipher` and `config::CipherConfig`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Mode {
+    Ecb,
+    Cbc,
+    Cfb,
+    Ofb,
+}
+
+impl Mode {
+    #[inline(always)]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mode::Ecb => "ecb",
+            Mode::Cbc => "cbc",
+            Mode::Cfb => "cfb",
+            Mode::Ofb => "ofb",
+        }
+    }
+
+    /// CFB and OFB are stream modes and need no padding.
+    #[inline(always)]
+    pub fn is_block_mode(&self) -> bool {
+        matches!(self, Mode::Ecb | Mode::Cbc)
+    }
+}
+
+impl FromStr for Mode {
+    type Err = Errors;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ecb" => Ok(Mode::Ecb),
+            "cbc" => Ok(Mode::Cbc),
+            "cfb" => Ok(Mode::Cfb),
+            "ofb" => Ok(Mode::Ofb),
+            _ => Err(Errors::InvalidMode),
+        }
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+macro_rules! require {
+    ($condition: expr, $err: expr) => {
+        if !$condition {
+            return Err($err);
+        }
+    };
+}
+
+#[inline(always)]
+fn xor(dst: &mut [u8], src: &[u8]) {
+    for (d, s) in dst.iter_mut().zip(src) {
+        *d ^= s
+    }
+}
+
+#[allow(clippy::manual_is_multiple_of)]
+#[inline(always)]
+pub fn ecb_encrypt(rijndael: &Rijndael, data: &[u8]) -> Result<Vec<u8>, Errors> {
+    require!(
+        data.len() % rijndael.block_size() == 0,
+        Errors::InvalidDataSize {
+            expected: Size::MultipleOf(rijndael.block_size()),
+            actual: data.len()
+        }
+    );
+    let mut result = Vec::with_capacity(data.len());
+    for block in data.chunks(rijndael.block_size()) {
+        result.extend(rijndael.encrypt(block)?);
+    }
+    Ok(result)
+}
+
+#[allow(clippy::manual_is_multiple_of)]
+#[inline(always)]
+pub fn ecb_decrypt(rijndael: &Rijndael, data: &[u8]) -> Result<Vec<u8>, Errors> {
+    require!(
+        data.len() % rijndael.block_size() == 0,
+        Errors::InvalidDataSize {
+            expected: Size::MultipleOf(rijndael.block_size()),
+            actual: data.len()
+        }
+    );
+    let mut result = Vec::with_capacity(data.len());
+    for block in data.chunks(rijndael.block_size()) {
+        result.extend(rijndael.decrypt(block)?);
+    }
+    Ok(result)
+}
+
+#[allow(clippy::manual_is_multiple_of)]
+#[inline(always)]
+pub fn cbc_encrypt(rijndael: &Rijndael, iv: &[u8], data: &[u8]) -> Result<Vec<u8>, Errors> {
+    require!(
+        iv.len() == rijndael.block_size(),
+        Errors::InvalidIvSize {
+            expected: Size::Exactly(rijndael.block_size()),
+            actual: iv.len()
+        }
+    );
+    require!(
+        data.len() % rijndael.block_size() == 0,
+        Errors::InvalidDataSize {
+            expected: Size::MultipleOf(rijndael.block_size()),
+            actual: data.len()
+        }
+    );
+    let mut result = Vec::with_capacity(data.len());
+    let mut v = iv.to_vec();
+    for block in data.chunks(rijndael.block_size()) {
+        xor(&mut v, block);
+        v = rijndael.encrypt(&v)?;
+        result.extend(&v);
+    }
+    Ok(result)
+}
+
+#[allow(clippy::manual_is_multiple_of)]
+#[inline(always)]
+pub fn cbc_decrypt(rijndael: &Rijndael, iv: &[u8], data: &[u8]) -> Result<Vec<u8>, Errors> {
+    require!(
+        iv.len() == rijndael.block_size(),
+        Errors::InvalidIvSize {
+            expected: Size::Exactly(rijndael.block_size()),
+            actual: iv.len()
+        }
+    );
+    require!(
+        data.len() % rijndael.block_size() == 0,
+        Errors::InvalidDataSize {
+            expected: Size::MultipleOf(rijndael.block_size()),
+            actual: data.len()
+        }
+    );
+    let mut result = Vec::with_capacity(data.len());
+    let mut v = iv;
+    for block in data.chunks(rijndael.block_size()) {
+        let mut decrypted = rijndael.decrypt(block)?;
+        xor(&mut decrypted, v);
+        result.extend(decrypted);
+        v = block;
+    }
+    Ok(result)
+}
+
+#[inline(always)]
+fn cfb(
+    rijndael: &Rijndael,
+    iv: &[u8],
+    segment_size: usize,
+    data: &[u8],
+    decrypt: bool,
+) -> Result<Vec<u8>, Errors> {
+    let block_size = rijndael.block_size();
+    require!(
+        iv.len() == block_size,
+        Errors::InvalidIvSize {
+            expected: Size::Exactly(block_size),
+            actual: iv.len()
+        }
+    );
+    require!(
+        segment_size > 0 && segment_size <= block_size,
+        Errors::InvalidSegmentSize {
+            expected: Size::Between(1, block_size),
+            actual: segment_size
+        }
+    );
+    let mut result = Vec::with_capacity(data.len());
+    let mut register = iv.to_vec();
+    for segment in data.chunks(segment_size) {
+        let mut output = rijndael.encrypt(&register)?;
+        output.truncate(segment.len());
+        xor(&mut output, segment);
+        // the register is fed with the ciphertext segment
+        let feedback = if decrypt { segment } else { &output };
+        register.drain(..feedback.len());
+        register.extend_from_slice(feedback);
+        result.extend(output);
+    }
+    Ok(result)
+}
+
+#[inline(always)]
+pub fn cfb_encrypt(
+    rijndael: &Rijndael,
+    iv: &[u8],
+    segment_size: usize,
+    data: &[u8],
+) -> Result<Vec<u8>, Errors> {
+    cfb(rijndael, iv, segment_size, data, false)
+}
+
+#[inline(always)]
+pub fn cfb_decrypt(
+    rijndael: &Rijndael,
+    iv: &[u8],
+    segment_size: usize,
+    data: &[u8],
+) -> Result<Vec<u8>, Errors> {
+    cfb(rijndael, iv, segment_size, data, true)
+}
+
+/// OFB is symmetric, the same function encrypts and decrypts.
+#[inline(always)]
+pub fn ofb(
+    rijndael: &Rijndael,
+    iv: &[u8],
+    segment_size: usize,
+    data: &[u8],
+) -> Result<Vec<u8>, Errors> {
+    let block_size = rijndael.block_size();
+    require!(
+        iv.len() == block_size,
+        Errors::InvalidIvSize {
+            expected: Size::Exactly(block_size),
+            actual: iv.len()
+        }
+    );
+    require!(
+        segment_size > 0 && segment_size <= block_size,
+        Errors::InvalidSegmentSize {
+            expected: Size::Between(1, block_size),
+            actual: segment_size
+        }
+    );
+    let mut result = Vec::with_capacity(data.len());
+    let mut register = iv.to_vec();
+    for segment in data.chunks(segment_size) {
+        let output = rijndael.encrypt(&register)?;
+        register.drain(..segment_size);
+        register.extend_from_slice(&output[..segment_size]);
+        let mut segment = segment.to_vec();
+        xor(&mut segment, &output);
+        result.extend(segment);
+    }
+    Ok(result)
+}
@@ -0,0 +1,166 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use md5::Md5;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    impls::RijndaelCbc,
+    kdf::{self, Prf},
+    paddings::Pkcs7Padding,
+    Errors, Size,
+};
+
+// Files of `openssl enc -aes-{128,192,256}-cbc`: `Salted__`, an 8 byte salt
+// and the PKCS#7 padded ciphertext, with key and IV derived from the password
+// and salt.
+
+pub const MAGIC: &[u8; 8] = b"Salted__";
+pub const SALT_SIZE: usize = 8;
+pub const BLOCK_SIZE: usize = 16;
+/// `openssl enc -pbkdf2` without `-iter`.
+pub const DEFAULT_ITERATIONS: u32 = 10000;
+
+/// The `-md` option.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageDigest {
+    Md5,
+    Sha256,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyDerivation {
+    /// `EVP_BytesToKey` with a single iteration, the legacy default (`-md md5`
+    /// before OpenSSL 1.1.0, `-md sha256` after).
+    BytesToKey(MessageDigest),
+    /// `-pbkdf2 -iter N`, PBKDF2-HMAC-SHA256.
+    Pbkdf2(u32),
+}
+
+impl KeyDerivation {
+    /// Returns the key of `key_size` bytes and the IV.
+    pub fn derive(
+        &self,
+        password: &[u8],
+        salt: &[u8],
+        key_size: usize,
+    ) -> Result<(Vec<u8>, Vec<u8>), Errors> {
+        let mut derived = match self {
+            KeyDerivation::BytesToKey(MessageDigest::Md5) => {
+                evp_bytes_to_key::<Md5>(password, salt, key_size + BLOCK_SIZE)
+            }
+            KeyDerivation::BytesToKey(MessageDigest::Sha256) => {
+                evp_bytes_to_key::<Sha256>(password, salt, key_size + BLOCK_SIZE)
+            }
+            KeyDerivation::Pbkdf2(iterations) => kdf::pbkdf2(
+                Prf::HmacSha256,
+                password,
+                salt,
+                *iterations,
+                key_size + BLOCK_SIZE,
+            )?,
+        };
+        let iv = derived.split_off(key_size);
+        Ok((derived, iv))
+    }
+}
+
+/// `EVP_BytesToKey` with one iteration: `D_i = HASH(D_(i-1) || password || salt)`
+/// concatenated until `size` bytes are available.
+pub fn evp_bytes_to_key<D: Digest>(password: &[u8], salt: &[u8], size: usize) -> Vec<u8> {
+    let mut derived = Vec::with_capacity(size);
+    let mut block: Vec<u8> = Vec::new();
+    while derived.len() < size {
+        let mut hasher = D::new();
+        hasher.update(&block);
+        hasher.update(password);
+        hasher.update(salt);
+        block = hasher.finalize().to_vec();
+        derived.extend_from_slice(&block);
+    }
+    derived.truncate(size);
+    derived
+}
+
+/// Splits a `Salted__` file into salt and ciphertext.
+#[inline(always)]
+pub fn parse_header(data: &[u8]) -> Result<(&[u8], &[u8]), Errors> {
+    if data.len() < MAGIC.len() + SALT_SIZE || !data.starts_with(MAGIC) {
+        return Err(Errors::InvalidHeader);
+    }
+    Ok(data[MAGIC.len()..].split_at(SALT_SIZE))
+}
+
+/// Settings of an `openssl enc -aes-<key_size * 8>-cbc` invocation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpensslEnc {
+    pub key_size: usize,
+    pub kdf: KeyDerivation,
+}
+
+impl OpensslEnc {
+    #[inline(always)]
+    pub fn new(key_size: usize, kdf: KeyDerivation) -> Self {
+        Self { key_size, kdf }
+    }
+
+    #[inline(always)]
+    fn cipher(
+        &self,
+        password: &[u8],
+        salt: &[u8],
+    ) -> Result<(RijndaelCbc<Pkcs7Padding>, Vec<u8>), Errors> {
+        let (key, iv) = self.kdf.derive(password, salt, self.key_size)?;
+        Ok((RijndaelCbc::new(&key, BLOCK_SIZE)?, iv))
+    }
+
+    pub fn encrypt(
+        &self,
+        password: &[u8],
+        salt: &[u8; SALT_SIZE],
+        data: Vec<u8>,
+    ) -> Result<Vec<u8>, Errors> {
+        let (cipher, iv) = self.cipher(password, salt)?;
+        let mut result = Vec::with_capacity(MAGIC.len() + SALT_SIZE + data.len() + BLOCK_SIZE);
+        result.extend_from_slice(MAGIC);
+        result.extend_from_slice(salt);
+        result.extend(cipher.encrypt(&iv, data)?);
+        Ok(result)
+    }
+
+    pub fn decrypt(&self, password: &[u8], data: &[u8]) -> Result<Vec<u8>, Errors> {
+        let (salt, ciphertext) = parse_header(data)?;
+        if ciphertext.is_empty() {
+            return Err(Errors::InvalidDataSize {
+                expected: Size::AtLeast(BLOCK_SIZE),
+                actual: 0,
+            });
+        }
+        let (cipher, iv) = self.cipher(password, salt)?;
+        cipher.decrypt(&iv, ciphertext.to_vec())
+    }
+
+    /// Same as `encrypt` with `-a`, base64 in lines of 64 characters.
+    pub fn encrypt_base64(
+        &self,
+        password: &[u8],
+        salt: &[u8; SALT_SIZE],
+        data: Vec<u8>,
+    ) -> Result<String, Errors> {
+        let encoded = STANDARD.encode(self.encrypt(password, salt, data)?);
+        let mut result = String::with_capacity(encoded.len() + encoded.len() / 64 + 1);
+        for line in encoded.as_bytes().chunks(64) {
+            // base64 output is ascii
+            result.push_str(std::str::from_utf8(line).unwrap_or_default());
+            result.push('\n');
+        }
+        Ok(result)
+    }
+
+    /// Same as `decrypt` with `-a`.
+    pub fn decrypt_base64(&self, password: &[u8], data: &str) -> Result<Vec<u8>, Errors> {
+        let encoded: String = data.split_whitespace().collect();
+        let data = STANDARD
+            .decode(encoded)
+            .map_err(|_| Errors::InvalidBase64)?;
+        self.decrypt(password, &data)
+    }
+}
@@ -0,0 +1,493 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    str::FromStr,
+    sync::{Arc, RwLock},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use crate::{impls::RijndaelCbc, paddings::Pkcs7Padding, Errors, Size};
+
+/// The score key is this prefix followed by the client version date
+/// (`osuver`), e.g. `osu!-scoreburgr---------20210520`.
+pub const KEY_PREFIX: &str = "osu!-scoreburgr---------";
+/// The client encrypts with `RijndaelManaged { BlockSize = 256, Padding = PKCS7 }`.
+pub const BLOCK_SIZE: usize = 32;
+
+#[inline(always)]
+pub fn key(osu_version: &str) -> Result<[u8; 32], Errors> {
+    let mut key = [0; 32];
+    let version = osu_version.trim().as_bytes();
+    if KEY_PREFIX.len() + version.len() != key.len() {
+        return Err(Errors::InvalidKeySize {
+            expected: Size::Exactly(key.len()),
+            actual: KEY_PREFIX.len() + version.len(),
+        });
+    }
+    key[..KEY_PREFIX.len()].copy_from_slice(KEY_PREFIX.as_bytes());
+    key[KEY_PREFIX.len()..].copy_from_slice(version);
+    Ok(key)
+}
+
+#[inline(always)]
+pub fn cipher(osu_version: &str) -> Result<RijndaelCbc<Pkcs7Padding>, Errors> {
+    RijndaelCbc::new(&key(osu_version)?, BLOCK_SIZE)
+}
+
+#[inline(always)]
+pub fn decode_base64(input: &str) -> Result<Vec<u8>, Errors> {
+    STANDARD
+        .decode(input.trim())
+        .map_err(|_| Errors::InvalidBase64)
+}
+
+/// Decrypts one of the encrypted base64 form fields of a score submission
+/// (`score`, `s`, `fs`) with the base64 `iv` field and the key of the `osuver`
+/// field.
+#[inline(always)]
+pub fn decrypt_field(field: &str, iv: &str, osu_version: &str) -> Result<String, Errors> {
+    decrypt_field_with(&cipher(osu_version)?, field, iv)
+}
+
+#[inline(always)]
+fn decrypt_field_with(
+    cipher: &RijndaelCbc<Pkcs7Padding>,
+    field: &str,
+    iv: &str,
+) -> Result<String, Errors> {
+    let decrypted = cipher.decrypt(&decode_base64(iv)?, decode_base64(field)?)?;
+    String::from_utf8(decrypted).map_err(|_| Errors::InvalidScoreData)
+}
+
+/// Reverse of `decrypt_field`, returns the base64 encrypted field.
+#[inline(always)]
+pub fn encrypt_field(value: &str, iv: &[u8], osu_version: &str) -> Result<String, Errors> {
+    let encrypted = cipher(osu_version)?.encrypt(iv, value.as_bytes().to_vec())?;
+    Ok(STANDARD.encode(encrypted))
+}
+
+#[inline(always)]
+pub fn decrypt_score(score: &str, iv: &str, osu_version: &str) -> Result<ScoreSubmission, Errors> {
+    decrypt_field(score, iv, osu_version)?.parse()
+}
+
+/// The form fields of a score submission request.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SubmissionForm<'a> {
+    pub score: &'a str,
+    pub iv: &'a str,
+    pub osu_version: &'a str,
+    /// The `s` field.
+    pub client_hash: Option<&'a str>,
+    /// The `fs` field.
+    pub storyboard_check: Option<&'a str>,
+}
+
+/// Decrypts the score data along with the optional client hash and
+/// storyboard check fields of the form.
+#[inline(always)]
+pub fn decrypt_submission(form: &SubmissionForm) -> Result<ScoreSubmission, Errors> {
+    decrypt_submission_with(&cipher(form.osu_version)?, form)
+}
+
+#[inline(always)]
+fn decrypt_submission_with(
+    cipher: &RijndaelCbc<Pkcs7Padding>,
+    form: &SubmissionForm,
+) -> Result<ScoreSubmission, Errors> {
+    let mut submission: ScoreSubmission =
+        decrypt_field_with(cipher, form.score, form.iv)?.parse()?;
+    if let Some(client_hash) = form.client_hash {
+        submission.client_hash = Some(decrypt_field_with(cipher, client_hash, form.iv)?.parse()?);
+    }
+    if let Some(storyboard_check) = form.storyboard_check {
+        submission.storyboard_check = Some(decrypt_field_with(cipher, storyboard_check, form.iv)?);
+    }
+    Ok(submission)
+}
+
+/// Reverse of `decrypt_score`, returns the base64 `score` and `iv` fields.
+#[inline(always)]
+pub fn encrypt_score(
+    submission: &ScoreSubmission,
+    iv: &[u8],
+    osu_version: &str,
+) -> Result<(String, String), Errors> {
+    Ok((
+        encrypt_field(&submission.to_string(), iv, osu_version)?,
+        STANDARD.encode(iv),
+    ))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Grade {
+    XH,
+    X,
+    SH,
+    S,
+    A,
+    B,
+    C,
+    D,
+    F,
+    N,
+}
+
+impl FromStr for Grade {
+    type Err = Errors;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "XH" => Grade::XH,
+            "X" => Grade::X,
+            "SH" => Grade::SH,
+            "S" => Grade::S,
+            "A" => Grade::A,
+            "B" => Grade::B,
+            "C" => Grade::C,
+            "D" => Grade::D,
+            "F" => Grade::F,
+            "N" => Grade::N,
+            _ => return Err(Errors::InvalidScoreData),
+        })
+    }
+}
+
+impl fmt::Display for Grade {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameMode {
+    Standard = 0,
+    Taiko = 1,
+    Catch = 2,
+    Mania = 3,
+}
+
+impl FromStr for GameMode {
+    type Err = Errors;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "0" => GameMode::Standard,
+            "1" => GameMode::Taiko,
+            "2" => GameMode::Catch,
+            "3" => GameMode::Mania,
+            _ => return Err(Errors::InvalidScoreData),
+        })
+    }
+}
+
+/// Decrypted `s` field: hashes of the client executable and the machine it runs
+/// on, separated by colons.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientHash {
+    pub osu_md5: String,
+    /// Network adapter (MAC) addresses, `.` separated on the wire.
+    pub adapters: Vec<String>,
+    pub adapters_md5: String,
+    pub uninstall_md5: String,
+    pub disk_signature_md5: String,
+}
+
+impl FromStr for ClientHash {
+    type Err = Errors;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.split(':').collect();
+        if fields.len() < 5 {
+            return Err(Errors::InvalidScoreData);
+        }
+        Ok(Self {
+            osu_md5: fields[0].into(),
+            adapters: fields[1]
+                .split('.')
+                .filter(|adapter| !adapter.is_empty())
+                .map(Into::into)
+                .collect(),
+            adapters_md5: fields[2].into(),
+            uninstall_md5: fields[3].into(),
+            disk_signature_md5: fields[4].into(),
+        })
+    }
+}
+
+impl fmt::Display for ClientHash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:", self.osu_md5)?;
+        for adapter in &self.adapters {
+            write!(f, "{}.", adapter)?;
+        }
+        write!(
+            f,
+            ":{}:{}:{}:",
+            self.adapters_md5, self.uninstall_md5, self.disk_signature_md5
+        )
+    }
+}
+
+/// The colon separated score data of an osu! score submission.
+///
+/// `username` and `version` are stored without the trailing spaces the client
+/// appends, the spaces after the version are counted into `client_flags`.
+/// `client_hash` and `storyboard_check` come from separate form fields and are
+/// only set by `decrypt_submission`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScoreSubmission {
+    pub beatmap_md5: String,
+    pub username: String,
+    pub checksum: String,
+    pub n300: u16,
+    pub n100: u16,
+    pub n50: u16,
+    pub ngeki: u16,
+    pub nkatu: u16,
+    pub nmiss: u16,
+    pub score: u32,
+    pub max_combo: u16,
+    pub perfect: bool,
+    pub grade: Grade,
+    pub mods: u32,
+    pub passed: bool,
+    pub mode: GameMode,
+    /// Client time formatted as `yyMMddHHmmss`.
+    pub timestamp: u64,
+    pub version: String,
+    pub client_flags: u32,
+    pub client_hash: Option<ClientHash>,
+    pub storyboard_check: Option<String>,
+}
+
+#[inline(always)]
+fn parse_field<T: FromStr>(field: &str) -> Result<T, Errors> {
+    field.parse().map_err(|_| Errors::InvalidScoreData)
+}
+
+#[inline(always)]
+fn parse_bool(field: &str) -> Result<bool, Errors> {
+    match field {
+        "True" => Ok(true),
+        "False" => Ok(false),
+        _ => Err(Errors::InvalidScoreData),
+    }
+}
+
+#[inline(always)]
+fn format_bool(value: bool) -> &'static str {
+    if value {
+        "True"
+    } else {
+        "False"
+    }
+}
+
+impl FromStr for ScoreSubmission {
+    type Err = Errors;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.split(':').collect();
+        if fields.len() < 18 {
+            return Err(Errors::InvalidScoreData);
+        }
+        Ok(Self {
+            beatmap_md5: fields[0].into(),
+            username: fields[1].trim_end().into(),
+            checksum: fields[2].into(),
+            n300: parse_field(fields[3])?,
+            n100: parse_field(fields[4])?,
+            n50: parse_field(fields[5])?,
+            ngeki: parse_field(fields[6])?,
+            nkatu: parse_field(fields[7])?,
+            nmiss: parse_field(fields[8])?,
+            score: parse_field(fields[9])?,
+            max_combo: parse_field(fields[10])?,
+            perfect: parse_bool(fields[11])?,
+            grade: fields[12].parse()?,
+            mods: parse_field(fields[13])?,
+            passed: parse_bool(fields[14])?,
+            mode: fields[15].parse()?,
+            timestamp: parse_field(fields[16])?,
+            version: fields[17].trim().into(),
+            client_flags: fields[17].matches(' ').count() as u32,
+            client_hash: None,
+            storyboard_check: None,
+        })
+    }
+}
+
+impl fmt::Display for ScoreSubmission {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}:{}:{}:{}:{}:{}:{}:{}:{}:{}:{}:{}:{}:{}:{:012}:{}{:flags$}",
+            self.beatmap_md5,
+            self.username,
+            self.checksum,
+            self.n300,
+            self.n100,
+            self.n50,
+            self.ngeki,
+            self.nkatu,
+            self.nmiss,
+            self.score,
+            self.max_combo,
+            format_bool(self.perfect),
+            self.grade,
+            self.mods,
+            format_bool(self.passed),
+            self.mode as u8,
+            self.timestamp,
+            self.version,
+            "",
+            flags = self.client_flags as usize,
+        )
+    }
+}
+
+/// Derived ciphers cached by `KeyRegistry::cipher`, later versions are
+/// expanded on every call.
+pub const MAX_CACHED_VERSIONS: usize = 1024;
+
+/// `osuver` dates, `YYYYMMDD`.
+#[inline(always)]
+fn is_date(osu_version: &str) -> bool {
+    let digits = osu_version.as_bytes();
+    if digits.len() != 8 || !digits.iter().all(u8::is_ascii_digit) {
+        return false;
+    }
+    let number = |range: std::ops::Range<usize>| {
+        digits[range]
+            .iter()
+            .fold(0, |n, digit| n * 10 + (digit - b'0') as usize)
+    };
+    (1..=12).contains(&number(4..6)) && (1..=31).contains(&number(6..8))
+}
+
+/// Maps client versions (`osuver`) to score keys and caches the expanded
+/// ciphers.
+///
+/// Versions without an explicit mapping use the key derived by `key`.
+/// Additional mappings (`"version" = "key"`) can be loaded from JSON or TOML.
+/// `osuver` comes from clients, so of the derived ciphers only those of
+/// `YYYYMMDD` dates are cached, at most `MAX_CACHED_VERSIONS`.
+#[derive(Debug, Default)]
+pub struct KeyRegistry {
+    keys: HashMap<String, Vec<u8>>,
+    ciphers: RwLock<HashMap<String, Arc<RijndaelCbc<Pkcs7Padding>>>>,
+}
+
+impl KeyRegistry {
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, osu_version: &str, key: &[u8]) -> Result<(), Errors> {
+        let osu_version = osu_version.trim();
+        // expand once to validate the key
+        let cipher = RijndaelCbc::new(key, BLOCK_SIZE)?;
+        self.keys.insert(osu_version.into(), key.into());
+        self.ciphers
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(osu_version.into(), Arc::new(cipher));
+        Ok(())
+    }
+
+    pub fn extend<I, V, K>(&mut self, mappings: I) -> Result<(), Errors>
+    where
+        I: IntoIterator<Item = (V, K)>,
+        V: AsRef<str>,
+        K: AsRef<[u8]>,
+    {
+        for (osu_version, key) in mappings {
+            self.insert(osu_version.as_ref(), key.as_ref())?;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "json")]
+    pub fn load_json(&mut self, source: &str) -> Result<(), Errors> {
+        let mappings: HashMap<String, String> =
+            serde_json::from_str(source).map_err(|_| Errors::InvalidKeyFile)?;
+        self.extend(mappings)
+    }
+
+    #[cfg(feature = "toml")]
+    pub fn load_toml(&mut self, source: &str) -> Result<(), Errors> {
+        let mappings: HashMap<String, String> =
+            toml::from_str(source).map_err(|_| Errors::InvalidKeyFile)?;
+        self.extend(mappings)
+    }
+
+    /// Loads a `.json` or `.toml` mapping file, chosen by extension.
+    #[cfg(any(feature = "json", feature = "toml"))]
+    pub fn load_file<T: AsRef<std::path::Path>>(&mut self, path: T) -> Result<(), Errors> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path).map_err(|_| Errors::InvalidKeyFile)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            #[cfg(feature = "json")]
+            Some("json") => self.load_json(&source),
+            #[cfg(feature = "toml")]
+            Some("toml") => self.load_toml(&source),
+            _ => Err(Errors::InvalidKeyFile),
+        }
+    }
+
+    #[inline(always)]
+    pub fn key(&self, osu_version: &str) -> Result<Vec<u8>, Errors> {
+        match self.keys.get(osu_version.trim()) {
+            Some(key) => Ok(key.clone()),
+            None => Ok(key(osu_version)?.into()),
+        }
+    }
+
+    pub fn cipher(&self, osu_version: &str) -> Result<Arc<RijndaelCbc<Pkcs7Padding>>, Errors> {
+        let osu_version = osu_version.trim();
+        if let Some(cipher) = self
+            .ciphers
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(osu_version)
+        {
+            return Ok(cipher.clone());
+        }
+        let cipher = Arc::new(cipher(osu_version)?);
+        if is_date(osu_version) {
+            let mut ciphers = self.ciphers.write().unwrap_or_else(|e| e.into_inner());
+            if ciphers.len() - self.keys.len() < MAX_CACHED_VERSIONS {
+                ciphers.insert(osu_version.into(), cipher.clone());
+            }
+        }
+        Ok(cipher)
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.ciphers.read().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline(always)]
+    pub fn decrypt_submission(&self, form: &SubmissionForm) -> Result<ScoreSubmission, Errors> {
+        decrypt_submission_with(&*self.cipher(form.osu_version)?, form)
+    }
+
+    #[inline(always)]
+    pub fn decrypt_field(
+        &self,
+        field: &str,
+        iv: &str,
+        osu_version: &str,
+    ) -> Result<String, Errors> {
+        decrypt_field_with(&*self.cipher(osu_version)?, field, iv)
+    }
+}
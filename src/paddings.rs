@@ -1,9 +1,12 @@
-use crate::Errors;
+use crate::{Errors, Size};
 
 macro_rules! ensure_size {
     ($source: ident, $self: ident) => {
         if ($source.len() % $self.0) != 0 {
-            return Err(Errors::InvalidDataSize);
+            return Err(Errors::InvalidDataSize {
+                expected: Size::MultipleOf($self.0),
+                actual: $source.len(),
+            });
         };
     };
 }
@@ -11,8 +14,9 @@ macro_rules! ensure_size {
 macro_rules! impl_default_members {
     () => {
         #[inline(always)]
-        fn new(block_size: usize) -> Self {
-            Self(block_size)
+        fn new(block_size: usize) -> Result<Self, Errors> {
+            check_block_size(block_size)?;
+            Ok(Self(block_size))
         }
 
         #[inline(always)]
@@ -22,14 +26,30 @@ macro_rules! impl_default_members {
     };
 }
 
+/// Padding lengths are stored in one byte, so block sizes are 1 to 255.
+#[inline(always)]
+fn check_block_size(block_size: usize) -> Result<(), Errors> {
+    if !(1..=255).contains(&block_size) {
+        return Err(Errors::InvalidBlockSize {
+            expected: Size::Between(1, 255),
+            actual: block_size,
+        });
+    }
+    Ok(())
+}
+
+/// Dyn-compatible, `new` is only available on sized paddings. `new` rejects
+/// block sizes outside 1 to 255 with `InvalidBlockSize`.
 pub trait Padding {
-    fn new(block_size: usize) -> Self;
+    fn new(block_size: usize) -> Result<Self, Errors>
+    where
+        Self: Sized;
     fn size(&self) -> usize;
     fn encode(&self, input_vec: Vec<u8>) -> Vec<u8>;
     fn decode(&self, source: Vec<u8>) -> Result<Vec<u8>, Errors>;
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ZeroPadding(usize);
 
 impl Padding for ZeroPadding {
@@ -62,7 +82,7 @@ impl Padding for ZeroPadding {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Pkcs7Padding(usize);
 
 impl Padding for Pkcs7Padding {
@@ -77,12 +97,201 @@ impl Padding for Pkcs7Padding {
     #[inline(always)]
     fn decode(&self, source: Vec<u8>) -> Result<Vec<u8>, Errors> {
         ensure_size!(source, self);
+        check_pkcs7(&source, self.0)?;
         let pad_size = source[source.len() - 1];
         let end = source.len() - pad_size as usize;
         Ok(source[..end].into())
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnsiX923Padding(usize);
+
+impl Padding for AnsiX923Padding {
+    impl_default_members!();
+
+    #[inline(always)]
+    fn encode(&self, input_vec: Vec<u8>) -> Vec<u8> {
+        let pad_size = self.0 - (input_vec.len() % self.0);
+        let mut result = pad_numbers(input_vec, 0, pad_size);
+        let last = result.len() - 1;
+        result[last] = pad_size as u8;
+        result
+    }
+
+    #[inline(always)]
+    fn decode(&self, source: Vec<u8>) -> Result<Vec<u8>, Errors> {
+        ensure_size!(source, self);
+        let end = padded_end(&source, self.0)?;
+        if source[end..(source.len() - 1)].iter().any(|b| *b != 0) {
+            return Err(Errors::InvalidPadding);
+        }
+        Ok(source[..end].into())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Iso10126Padding(usize);
+
+impl Padding for Iso10126Padding {
+    impl_default_members!();
+
+    #[inline(always)]
+    fn encode(&self, mut input_vec: Vec<u8>) -> Vec<u8> {
+        let pad_size = self.0 - (input_vec.len() % self.0);
+        let length = input_vec.len();
+        input_vec.resize(length + pad_size, pad_size as u8);
+        random_fill(&mut input_vec[length..(length + pad_size - 1)]);
+        input_vec
+    }
+
+    #[inline(always)]
+    fn decode(&self, source: Vec<u8>) -> Result<Vec<u8>, Errors> {
+        ensure_size!(source, self);
+        let end = padded_end(&source, self.0)?;
+        Ok(source[..end].into())
+    }
+}
+
+/// One of the paddings above, chosen at runtime, e.g. from a name in a
+/// config file. `Padding::new` gives PKCS#7.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaddingKind {
+    Zero(ZeroPadding),
+    Pkcs7(Pkcs7Padding),
+    AnsiX923(AnsiX923Padding),
+    Iso10126(Iso10126Padding),
+}
+
+impl PaddingKind {
+    /// `name` is `"zero"`, `"pkcs7"`, `"ansix923"` or `"iso10126"`.
+    pub fn from_name(name: &str, block_size: usize) -> Result<Self, Errors> {
+        match name {
+            "zero" => Ok(PaddingKind::Zero(ZeroPadding::new(block_size)?)),
+            "pkcs7" => Ok(PaddingKind::Pkcs7(Pkcs7Padding::new(block_size)?)),
+            "ansix923" => Ok(PaddingKind::AnsiX923(AnsiX923Padding::new(block_size)?)),
+            "iso10126" => Ok(PaddingKind::Iso10126(Iso10126Padding::new(block_size)?)),
+            _ => Err(Errors::InvalidPadding),
+        }
+    }
+
+    #[inline(always)]
+    pub fn name(&self) -> &'static str {
+        match self {
+            PaddingKind::Zero(_) => "zero",
+            PaddingKind::Pkcs7(_) => "pkcs7",
+            PaddingKind::AnsiX923(_) => "ansix923",
+            PaddingKind::Iso10126(_) => "iso10126",
+        }
+    }
+
+    #[inline(always)]
+    fn as_dyn(&self) -> &dyn Padding {
+        match self {
+            PaddingKind::Zero(padding) => padding,
+            PaddingKind::Pkcs7(padding) => padding,
+            PaddingKind::AnsiX923(padding) => padding,
+            PaddingKind::Iso10126(padding) => padding,
+        }
+    }
+}
+
+impl Padding for PaddingKind {
+    /// Always PKCS#7, e.g. for `RijndaelCbc::<PaddingKind>::new`. Other
+    /// paddings are built with `from_name` or the variants and passed to
+    /// `RijndaelCbc::with_padding`.
+    #[inline(always)]
+    fn new(block_size: usize) -> Result<Self, Errors> {
+        Ok(PaddingKind::Pkcs7(Pkcs7Padding::new(block_size)?))
+    }
+
+    #[inline(always)]
+    fn size(&self) -> usize {
+        self.as_dyn().size()
+    }
+
+    #[inline(always)]
+    fn encode(&self, input_vec: Vec<u8>) -> Vec<u8> {
+        self.as_dyn().encode(input_vec)
+    }
+
+    #[inline(always)]
+    fn decode(&self, source: Vec<u8>) -> Result<Vec<u8>, Errors> {
+        self.as_dyn().decode(source)
+    }
+}
+
+/// Boxed paddings for `RijndaelCbc<Box<dyn Padding>>`, built with
+/// `RijndaelCbc::with_padding`. Like for `PaddingKind`, `new` gives PKCS#7.
+macro_rules! impl_boxed {
+    ($($bounds: tt)*) => {
+        impl Padding for Box<dyn Padding $($bounds)*> {
+            #[inline(always)]
+            fn new(block_size: usize) -> Result<Self, Errors> {
+                Ok(Box::new(Pkcs7Padding::new(block_size)?))
+            }
+
+            #[inline(always)]
+            fn size(&self) -> usize {
+                (**self).size()
+            }
+
+            #[inline(always)]
+            fn encode(&self, input_vec: Vec<u8>) -> Vec<u8> {
+                (**self).encode(input_vec)
+            }
+
+            #[inline(always)]
+            fn decode(&self, source: Vec<u8>) -> Result<Vec<u8>, Errors> {
+                (**self).decode(source)
+            }
+        }
+    };
+}
+
+impl_boxed!();
+impl_boxed!(+ Send + Sync);
+
+/// Strict PKCS#7 check: every padding byte must hold the padding length.
+#[inline(always)]
+fn check_pkcs7(source: &[u8], block_size: usize) -> Result<(), Errors> {
+    let end = padded_end(source, block_size)?;
+    if source[end..]
+        .iter()
+        .any(|b| *b as usize != source.len() - end)
+    {
+        return Err(Errors::InvalidPadding);
+    }
+    Ok(())
+}
+
+/// Start of the padding for schemes that store the padding length in the
+/// last byte.
+#[inline(always)]
+fn padded_end(source: &[u8], block_size: usize) -> Result<usize, Errors> {
+    let pad_size = *source.last().ok_or(Errors::InvalidPadding)? as usize;
+    if pad_size == 0 || pad_size > block_size || pad_size > source.len() {
+        return Err(Errors::InvalidPadding);
+    }
+    Ok(source.len() - pad_size)
+}
+
+/// ISO 10126 padding bytes only need to be arbitrary, not secret, so the
+/// randomly seeded std hasher is good enough and avoids an rng dependency.
+fn random_fill(dst: &mut [u8]) {
+    use std::{
+        collections::hash_map::RandomState,
+        hash::{BuildHasher, Hasher},
+    };
+
+    let state = RandomState::new();
+    for (i, chunk) in dst.chunks_mut(8).enumerate() {
+        let mut hasher = state.build_hasher();
+        hasher.write_usize(i);
+        chunk.copy_from_slice(&hasher.finish().to_le_bytes()[..chunk.len()]);
+    }
+}
+
 fn pad_numbers(mut dst: Vec<u8>, src: u8, pad_size: usize) -> Vec<u8> {
     dst.reserve(pad_size);
     let length = dst.len();
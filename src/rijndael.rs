@@ -1,4 +1,9 @@
-use crate::{constants::*, Errors};
+use crate::{
+    constants::*,
+    tables,
+    types::{Block, Key},
+    Errors, Size,
+};
 
 macro_rules! require {
     ($condition: expr, $err: expr) => {
@@ -41,16 +46,41 @@ impl Rijndael {
 
     #[inline(always)]
     pub fn new(key: &[u8], block_size: usize) -> Result<Self, Errors> {
-        require!(VALID.contains(&block_size), Errors::InvalidBlockSize);
-        require!(VALID.contains(&key.len()), Errors::InvalidKeySize);
+        require!(
+            VALID.contains(&block_size),
+            Errors::InvalidBlockSize {
+                expected: Size::Rijndael,
+                actual: block_size
+            }
+        );
+        let key = Key::new(key)?;
+        Ok(Self::expand(&key, block_size))
+    }
+
+    /// Same as `new` in const context, so a fixed key can be expanded at
+    /// compile time: `static CIPHER: Rijndael = Rijndael::new_const(KEY, 32);`.
+    /// Invalid sizes fail the build (or panic when called at runtime).
+    pub const fn new_const(key: &[u8], block_size: usize) -> Self {
+        assert!(
+            matches!(block_size, 16 | 24 | 32),
+            "block size must be 16, 24 or 32 bytes"
+        );
+        assert!(
+            matches!(key.len(), 16 | 24 | 32),
+            "key size must be 16, 24 or 32 bytes"
+        );
+        Self::expand(key, block_size)
+    }
+
+    /// Key schedule, sizes are checked by the callers.
+    #[allow(unused_parens, clippy::identity_op)]
+    const fn expand(key: &[u8], block_size: usize) -> Self {
         let rounds = if block_size == 32 || key.len() == 32 {
             14
+        } else if block_size == 16 && key.len() == 16 {
+            10
         } else {
-            if block_size == 16 && key.len() == 16 {
-                10
-            } else {
-                12
-            }
+            12
         };
         let b_c = block_size / 4;
         let s_c = match b_c {
@@ -67,12 +97,14 @@ impl Rijndael {
 
         // copy user material bytes into temporary ints
         let mut tk = EMPTY_BLOCK;
-        for i in 0..k_c {
+        let mut i = 0;
+        while i < k_c {
             let s = 4 * i;
             tk[i] = ((key[s] as u32) << 24)
                 | ((key[s + 1] as u32) << 16)
                 | ((key[s + 2] as u32) << 8)
                 | (key[s + 3] as u32);
+            i += 1;
         }
 
         let mut t = 0;
@@ -84,7 +116,7 @@ impl Rijndael {
             t += 1;
         }
         let mut r_con_pointer = 0;
-        loop {
+        while t < round_key_count {
             let mut tt = tk[k_c - 1];
             tk[0] ^= ((S[(tt >> 16) as usize & 0xFF] & 0xFF) as u32) << 24
                 ^ ((S[(tt >> 8) as usize & 0xFF] & 0xFF) as u32) << 16
@@ -94,20 +126,26 @@ impl Rijndael {
 
             r_con_pointer += 1;
             if k_c != 8 {
-                for i in 1..k_c {
+                let mut i = 1;
+                while i < k_c {
                     tk[i] ^= tk[i - 1];
+                    i += 1;
                 }
             } else {
-                for i in 1..(k_c / 2) {
+                let mut i = 1;
+                while i < k_c / 2 {
                     tk[i] ^= tk[i - 1];
+                    i += 1;
                 }
                 tt = tk[((k_c / 2) - 1)];
                 tk[(k_c / 2)] ^= ((S[tt as usize & 0xFF] & 0xFF) as u32)
                     ^ ((S[(tt >> 8) as usize & 0xFF] & 0xFF) as u32) << 8
                     ^ ((S[(tt >> 16) as usize & 0xFF] & 0xFF) as u32) << 16
                     ^ ((S[(tt >> 24) as usize & 0xFF] & 0xFF) as u32) << 24;
-                for i in ((k_c / 2) + 1)..k_c {
+                let mut i = (k_c / 2) + 1;
+                while i < k_c {
                     tk[i] ^= tk[(i - 1)];
+                    i += 1;
                 }
             }
             j = 0;
@@ -117,32 +155,30 @@ impl Rijndael {
                 j += 1;
                 t += 1;
             }
-            if t >= round_key_count {
-                break;
-            }
         }
-        for r in 1..rounds {
-            for j in 0..b_c {
-                let tt = k_d[r][j];
-                k_d[r][j] = U1[(tt >> 24) as usize & 0xFF]
-                    ^ U2[(tt >> 16) as usize & 0xFF]
-                    ^ U3[(tt >> 8) as usize & 0xFF]
-                    ^ U4[tt as usize & 0xFF];
+        let mut r = 1;
+        while r < rounds {
+            let mut j = 0;
+            while j < b_c {
+                k_d[r][j] = tables::inv_mix_column(k_d[r][j]);
+                j += 1;
             }
+            r += 1;
         }
-        Ok(Self {
+        Self {
             block_size,
             rounds,
             b_c,
             s_c,
             k_e,
             k_d,
-        })
+        }
     }
 
     #[inline(always)]
+    #[allow(unused_parens)]
     pub fn encrypt(&self, source: &[u8]) -> Result<Vec<u8>, Errors> {
-        require!(source.len() == self.block_size, Errors::InvalidBlockSize);
+        let source = Block::new(source, self.block_size)?;
         let s1 = SHIFTS[self.s_c][1][0];
         let s2 = SHIFTS[self.s_c][2][0];
         let s3 = SHIFTS[self.s_c][3][0];
@@ -150,6 +186,7 @@ impl Rijndael {
         // temporary work array
         let mut t = EMPTY_BLOCK;
         // source to ints + key
+        #[allow(clippy::needless_range_loop)]
         for i in 0..self.b_c {
             let s = 4 * i;
             t[i] = ((source[s] as u32) << 24
@@ -161,11 +198,12 @@ impl Rijndael {
         // apply round transforms
         for r in 1..self.rounds {
             for i in 0..self.b_c {
-                a[i] = (T1[(t[i] >> 24) as usize & 0xFF]
-                    ^ T2[(t[(i + s1 as usize) % self.b_c] >> 16) as usize & 0xFF]
-                    ^ T3[(t[(i + s2 as usize) % self.b_c] >> 8) as usize & 0xFF]
-                    ^ T4[t[(i + s3 as usize) % self.b_c] as usize & 0xFF])
-                    ^ self.k_e[r][i];
+                a[i] = tables::encrypt_round(
+                    t[i] >> 24,
+                    t[(i + s1 as usize) % self.b_c] >> 16,
+                    t[(i + s2 as usize) % self.b_c] >> 8,
+                    t[(i + s3 as usize) % self.b_c],
+                ) ^ self.k_e[r][i];
             }
             t = a;
         }
@@ -188,16 +226,15 @@ impl Rijndael {
     }
 
     #[inline(always)]
+    #[allow(unused_parens)]
     pub fn decrypt(&self, block_cipher: &[u8]) -> Result<Vec<u8>, Errors> {
-        require!(
-            block_cipher.len() == self.block_size,
-            Errors::InvalidBlockSize
-        );
+        let block_cipher = Block::new(block_cipher, self.block_size)?;
         let s1 = SHIFTS[self.s_c][1][1];
         let s2 = SHIFTS[self.s_c][2][1];
         let s3 = SHIFTS[self.s_c][3][1];
         let mut a = EMPTY_BLOCK;
         let mut t = EMPTY_BLOCK;
+        #[allow(clippy::needless_range_loop)]
         for i in 0..self.b_c {
             let s = 4 * i;
             t[i] = ((block_cipher[s] as u32) << 24
@@ -208,11 +245,12 @@ impl Rijndael {
         }
         for r in 1..self.rounds {
             for i in 0..self.b_c {
-                a[i] = (T5[(t[i] >> 24) as usize & 0xFF]
-                    ^ T6[(t[(i + s1 as usize) % self.b_c] >> 16) as usize & 0xFF]
-                    ^ T7[(t[(i + s2 as usize) % self.b_c] >> 8) as usize & 0xFF]
-                    ^ T8[t[(i + s3 as usize) % self.b_c] as usize & 0xFF])
-                    ^ self.k_d[r][i];
+                a[i] = tables::decrypt_round(
+                    t[i] >> 24,
+                    t[(i + s1 as usize) % self.b_c] >> 16,
+                    t[(i + s2 as usize) % self.b_c] >> 8,
+                    t[(i + s3 as usize) % self.b_c],
+                ) ^ self.k_d[r][i];
             }
             t = a;
         }
@@ -0,0 +1,112 @@
+use crate::constants::*;
+
+// Round lookups of `Rijndael`, each argument is one byte of the state.
+//
+// - default: `T1`-`T8` and `U1`-`U4`, 12 KiB
+// - `small-tables`: `T1` and `T5` rotated per column (`T2[x]` is
+//   `T1[x].rotate_right(8)` and so on), `U` from `T5` through the S-box, 2 KiB
+// - `tiny-tables`: only the S-boxes, (Inv)MixColumns computed with `xtime`
+
+#[cfg(not(feature = "small-tables"))]
+#[inline(always)]
+pub(crate) const fn encrypt_round(a: u32, b: u32, c: u32, d: u32) -> u32 {
+    T1[a as usize & 0xFF] ^ T2[b as usize & 0xFF] ^ T3[c as usize & 0xFF] ^ T4[d as usize & 0xFF]
+}
+
+#[cfg(not(feature = "small-tables"))]
+#[inline(always)]
+pub(crate) const fn decrypt_round(a: u32, b: u32, c: u32, d: u32) -> u32 {
+    T5[a as usize & 0xFF] ^ T6[b as usize & 0xFF] ^ T7[c as usize & 0xFF] ^ T8[d as usize & 0xFF]
+}
+
+/// InvMixColumns of a round key word.
+#[cfg(not(feature = "small-tables"))]
+#[inline(always)]
+pub(crate) const fn inv_mix_column(w: u32) -> u32 {
+    U1[(w >> 24) as usize & 0xFF]
+        ^ U2[(w >> 16) as usize & 0xFF]
+        ^ U3[(w >> 8) as usize & 0xFF]
+        ^ U4[w as usize & 0xFF]
+}
+
+#[cfg(all(feature = "small-tables", not(feature = "tiny-tables")))]
+#[inline(always)]
+pub(crate) const fn encrypt_round(a: u32, b: u32, c: u32, d: u32) -> u32 {
+    T1[a as usize & 0xFF]
+        ^ T1[b as usize & 0xFF].rotate_right(8)
+        ^ T1[c as usize & 0xFF].rotate_right(16)
+        ^ T1[d as usize & 0xFF].rotate_right(24)
+}
+
+#[cfg(all(feature = "small-tables", not(feature = "tiny-tables")))]
+#[inline(always)]
+pub(crate) const fn decrypt_round(a: u32, b: u32, c: u32, d: u32) -> u32 {
+    T5[a as usize & 0xFF]
+        ^ T5[b as usize & 0xFF].rotate_right(8)
+        ^ T5[c as usize & 0xFF].rotate_right(16)
+        ^ T5[d as usize & 0xFF].rotate_right(24)
+}
+
+#[cfg(all(feature = "small-tables", not(feature = "tiny-tables")))]
+#[inline(always)]
+pub(crate) const fn inv_mix_column(w: u32) -> u32 {
+    // U1[x] == T5[S[x]]
+    decrypt_round(
+        S[(w >> 24) as usize & 0xFF] as u32,
+        S[(w >> 16) as usize & 0xFF] as u32,
+        S[(w >> 8) as usize & 0xFF] as u32,
+        S[w as usize & 0xFF] as u32,
+    )
+}
+
+/// Multiplication by x, without a branch on the value.
+#[cfg(feature = "tiny-tables")]
+#[inline(always)]
+const fn xtime(a: u8) -> u8 {
+    (a << 1) ^ ((a >> 7) * 0x1b)
+}
+
+/// `mul_row(a, G[0])`, {02}a {01}a {01}a {03}a.
+#[cfg(feature = "tiny-tables")]
+#[inline(always)]
+const fn mix(a: u8) -> u32 {
+    let a2 = xtime(a);
+    u32::from_be_bytes([a2, a, a, a2 ^ a])
+}
+
+/// `mul_row(a, IG[0])`, {0e}a {09}a {0d}a {0b}a.
+#[cfg(feature = "tiny-tables")]
+#[inline(always)]
+const fn inv_mix(a: u8) -> u32 {
+    let a2 = xtime(a);
+    let a4 = xtime(a2);
+    let a8 = xtime(a4);
+    u32::from_be_bytes([a8 ^ a4 ^ a2, a8 ^ a, a8 ^ a4 ^ a, a8 ^ a2 ^ a])
+}
+
+#[cfg(feature = "tiny-tables")]
+#[inline(always)]
+pub(crate) const fn encrypt_round(a: u32, b: u32, c: u32, d: u32) -> u32 {
+    mix(S[a as usize & 0xFF])
+        ^ mix(S[b as usize & 0xFF]).rotate_right(8)
+        ^ mix(S[c as usize & 0xFF]).rotate_right(16)
+        ^ mix(S[d as usize & 0xFF]).rotate_right(24)
+}
+
+#[cfg(feature = "tiny-tables")]
+#[inline(always)]
+pub(crate) const fn decrypt_round(a: u32, b: u32, c: u32, d: u32) -> u32 {
+    inv_mix(SI[a as usize & 0xFF])
+        ^ inv_mix(SI[b as usize & 0xFF]).rotate_right(8)
+        ^ inv_mix(SI[c as usize & 0xFF]).rotate_right(16)
+        ^ inv_mix(SI[d as usize & 0xFF]).rotate_right(24)
+}
+
+#[cfg(feature = "tiny-tables")]
+#[inline(always)]
+pub(crate) const fn inv_mix_column(w: u32) -> u32 {
+    inv_mix((w >> 24) as u8)
+        ^ inv_mix((w >> 16) as u8).rotate_right(8)
+        ^ inv_mix((w >> 8) as u8).rotate_right(16)
+        ^ inv_mix(w as u8).rotate_right(24)
+}
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use crate::impls::RijndaelCbc;
 use crate::paddings::ZeroPadding;
 
@@ -72,3 +74,1391 @@ fn test_pkcs7() {
     let decrypted_result = r.decrypt(iv, result).unwrap();
     assert_eq!(decrypted_result, test_data);
 }
+
+#[test]
+fn test_incremental() {
+    let r = Arc::new(RijndaelCbc::<ZeroPadding>::new(OSU_KEY, 32).unwrap());
+
+    let mut encrypted = Vec::new();
+    let mut encryptor = r.encryptor(&OSU_IV).unwrap();
+    for chunk in OSU_DECRYPTED.chunks(7) {
+        encryptor.update(chunk, &mut encrypted).unwrap();
+    }
+    encryptor.finalize(&mut encrypted).unwrap();
+    assert_eq!(encrypted, OSU_CRYPTED.to_vec());
+
+    let mut decrypted = Vec::new();
+    let mut decryptor = r.decryptor(&OSU_IV).unwrap();
+    for chunk in OSU_CRYPTED.chunks(45) {
+        decryptor.update(chunk, &mut decrypted).unwrap();
+    }
+    decryptor.finalize(&mut decrypted).unwrap();
+    assert_eq!(decrypted, OSU_DECRYPTED.to_vec());
+}
+
+#[test]
+fn test_incremental_pkcs7() {
+    use crate::paddings::Pkcs7Padding;
+
+    let test_data = b"This string is used to test Pkcs7Padding".to_vec();
+    let iv = b"128 bit iv_ test";
+    let r = Arc::new(RijndaelCbc::<Pkcs7Padding>::new(b"128 bit key test", 16).unwrap());
+
+    let mut encrypted = Vec::new();
+    let mut encryptor = r.encryptor(iv).unwrap();
+    encryptor.update(&test_data[..3], &mut encrypted).unwrap();
+    encryptor.update(&test_data[3..], &mut encrypted).unwrap();
+    encryptor.finalize(&mut encrypted).unwrap();
+    assert_eq!(encrypted, r.encrypt(iv, test_data.clone()).unwrap());
+
+    let mut decrypted = Vec::new();
+    let mut decryptor = r.decryptor(iv).unwrap();
+    decryptor.update(&encrypted, &mut decrypted).unwrap();
+    assert_eq!(decrypted.len(), 32);
+    decryptor.finalize(&mut decrypted).unwrap();
+    assert_eq!(decrypted, test_data);
+
+    let mut decryptor = r.decryptor(iv).unwrap();
+    decryptor.update(&encrypted[..20], &mut decrypted).unwrap();
+    assert!(decryptor.finalize(&mut decrypted).is_err());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn test_async_io() {
+    use crate::async_io::{CbcReader, CbcWriter};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let r = Arc::new(RijndaelCbc::<ZeroPadding>::new(OSU_KEY, 32).unwrap());
+
+    // small duplex buffer so the writer has to wait for the reader
+    let (client, mut server) = tokio::io::duplex(64);
+    let mut writer = CbcWriter::new(client, r.encryptor(&OSU_IV).unwrap());
+    let write = tokio::spawn(async move {
+        for chunk in OSU_DECRYPTED.chunks(13) {
+            writer.write_all(chunk).await.unwrap();
+        }
+        writer.shutdown().await.unwrap();
+    });
+    let mut encrypted = Vec::new();
+    server.read_to_end(&mut encrypted).await.unwrap();
+    write.await.unwrap();
+    assert_eq!(encrypted, OSU_CRYPTED.to_vec());
+
+    let (mut client, server) = tokio::io::duplex(64);
+    let mut reader = CbcReader::new(server, r.decryptor(&OSU_IV).unwrap());
+    let write = async {
+        client.write_all(&OSU_CRYPTED).await.unwrap();
+        client.shutdown().await.unwrap();
+    };
+    let mut decrypted = Vec::new();
+    let read = reader.read_to_end(&mut decrypted);
+    let (_, read) = tokio::join!(write, read);
+    read.unwrap();
+    assert_eq!(decrypted, OSU_DECRYPTED.to_vec());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn test_async_io_truncated() {
+    use crate::async_io::CbcReader;
+    use crate::paddings::Pkcs7Padding;
+    use tokio::io::AsyncReadExt;
+
+    let r = Arc::new(RijndaelCbc::<Pkcs7Padding>::new(b"128 bit key test", 16).unwrap());
+    let encrypted = r
+        .encrypt(b"128 bit iv_ test", b"truncated".to_vec())
+        .unwrap();
+    let mut reader = CbcReader::new(&encrypted[..10], r.decryptor(b"128 bit iv_ test").unwrap());
+    let err = reader.read_to_end(&mut Vec::new()).await.unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[cfg(feature = "osu")]
+#[test]
+fn test_osu_score() {
+    use crate::osu::{self, GameMode, Grade};
+    use base64::{engine::general_purpose::STANDARD, Engine};
+
+    let score = STANDARD.encode(OSU_CRYPTED);
+    let iv = STANDARD.encode(OSU_IV);
+    let submission = osu::decrypt_score(&score, &iv, "20210520").unwrap();
+    assert_eq!(submission.beatmap_md5, "c51aee56bb5195244252d190baa54b49");
+    assert_eq!(submission.username, "PurePeace");
+    assert_eq!(submission.checksum, "815772625adad639735a044e8aba9360");
+    assert_eq!(
+        (
+            submission.n300,
+            submission.n100,
+            submission.n50,
+            submission.ngeki,
+            submission.nkatu,
+            submission.nmiss
+        ),
+        (44, 18, 6, 4, 6, 20)
+    );
+    assert_eq!(submission.score, 38645);
+    assert_eq!(submission.max_combo, 26);
+    assert!(!submission.perfect);
+    assert_eq!(submission.grade, Grade::F);
+    assert_eq!(submission.mods, 64);
+    assert!(!submission.passed);
+    assert_eq!(submission.mode, GameMode::Standard);
+    assert_eq!(submission.timestamp, 210615064431);
+    assert_eq!(submission.version, "20210520");
+    assert_eq!(submission.client_flags, 0);
+
+    let (score, iv) = osu::encrypt_score(&submission, &OSU_IV, "20210520").unwrap();
+    assert_eq!(
+        osu::decrypt_score(&score, &iv, "20210520").unwrap(),
+        submission
+    );
+
+    assert!(osu::decrypt_score(&score, &iv, "2021052").is_err());
+    assert!(osu::decrypt_score("not base64!", &iv, "20210520").is_err());
+    assert!("c51aee56bb5195244252d190baa54b49:PurePeace"
+        .parse::<osu::ScoreSubmission>()
+        .is_err());
+}
+
+#[cfg(feature = "osu")]
+#[test]
+fn test_osu_score_block_aligned() {
+    use crate::{osu, paddings::Pkcs7Padding};
+    use base64::{engine::general_purpose::STANDARD, Engine};
+
+    let mut submission = osu::decrypt_score(
+        &STANDARD.encode(OSU_CRYPTED),
+        &STANDARD.encode(OSU_IV),
+        "20210520",
+    )
+    .unwrap();
+    submission.client_flags = 3;
+    // PKCS#7 adds a whole block of 0x20 to aligned data, which must not be
+    // counted as client flags
+    let len = submission.to_string().len();
+    submission.username.push_str(&"x".repeat(32 - len % 32));
+    assert_eq!(submission.to_string().len() % 32, 0);
+
+    let (score, iv) = osu::encrypt_score(&submission, &OSU_IV, "20210520").unwrap();
+    assert_eq!(
+        STANDARD.decode(&score).unwrap().len(),
+        submission.to_string().len() + 32
+    );
+    let decrypted = osu::decrypt_score(&score, &iv, "20210520").unwrap();
+    assert_eq!(decrypted.client_flags, 3);
+    assert_eq!(decrypted, submission);
+
+    // encrypted like the client does
+    let client = RijndaelCbc::<Pkcs7Padding>::new(OSU_KEY, 32)
+        .unwrap()
+        .encrypt(&OSU_IV, submission.to_string().into_bytes())
+        .unwrap();
+    let decrypted = osu::decrypt_score(&STANDARD.encode(client), &iv, "20210520").unwrap();
+    assert_eq!(decrypted.client_flags, 3);
+}
+
+#[cfg(feature = "osu")]
+#[test]
+fn test_osu_client_hash() {
+    use crate::osu::{self, ClientHash, SubmissionForm};
+    use base64::{engine::general_purpose::STANDARD, Engine};
+
+    let raw = "0ae8b6ecff7fe3ee1d0bd2c0bb4e2fa8:00155D4B2C7F.0A0027000013.:\
+               3c3d1d7e44b2e9e60e47a2d1f0aaeaf8:bd4c2d31fcc5a6d8e8e3ec7e5e1e2b0d:\
+               d41d8cd98f00b204e9800998ecf8427e:";
+    let client_hash: ClientHash = raw.parse().unwrap();
+    assert_eq!(client_hash.osu_md5, "0ae8b6ecff7fe3ee1d0bd2c0bb4e2fa8");
+    assert_eq!(client_hash.adapters, vec!["00155D4B2C7F", "0A0027000013"]);
+    assert_eq!(client_hash.adapters_md5, "3c3d1d7e44b2e9e60e47a2d1f0aaeaf8");
+    assert_eq!(
+        client_hash.uninstall_md5,
+        "bd4c2d31fcc5a6d8e8e3ec7e5e1e2b0d"
+    );
+    assert_eq!(
+        client_hash.disk_signature_md5,
+        "d41d8cd98f00b204e9800998ecf8427e"
+    );
+    assert_eq!(client_hash.to_string(), raw);
+
+    let score = STANDARD.encode(OSU_CRYPTED);
+    let iv = STANDARD.encode(OSU_IV);
+    let s = osu::encrypt_field(raw, &OSU_IV, "20210520").unwrap();
+    let fs = osu::encrypt_field("storyboard", &OSU_IV, "20210520").unwrap();
+    let submission = osu::decrypt_submission(&SubmissionForm {
+        score: &score,
+        iv: &iv,
+        osu_version: "20210520",
+        client_hash: Some(&s),
+        storyboard_check: Some(&fs),
+    })
+    .unwrap();
+    assert_eq!(submission.username, "PurePeace");
+    assert_eq!(submission.client_hash, Some(client_hash));
+    assert_eq!(submission.storyboard_check.as_deref(), Some("storyboard"));
+
+    assert!("0ae8b6ecff7fe3ee1d0bd2c0bb4e2fa8:"
+        .parse::<ClientHash>()
+        .is_err());
+}
+
+#[cfg(feature = "osu")]
+#[test]
+fn test_osu_key_registry() {
+    use crate::osu::{KeyRegistry, SubmissionForm, MAX_CACHED_VERSIONS};
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use std::sync::Arc;
+
+    let score = STANDARD.encode(OSU_CRYPTED);
+    let iv = STANDARD.encode(OSU_IV);
+    let mut registry = KeyRegistry::new();
+    assert!(registry.is_empty());
+
+    let form = SubmissionForm {
+        score: &score,
+        iv: &iv,
+        osu_version: "20210520",
+        ..Default::default()
+    };
+    assert_eq!(
+        registry.decrypt_submission(&form).unwrap().username,
+        "PurePeace"
+    );
+    assert!(Arc::ptr_eq(
+        &registry.cipher("20210520").unwrap(),
+        &registry.cipher(" 20210520 ").unwrap()
+    ));
+    assert_eq!(registry.len(), 1);
+
+    // explicit mappings take precedence over the derived key
+    registry.insert("custom", OSU_KEY).unwrap();
+    assert_eq!(registry.key("custom").unwrap(), OSU_KEY.to_vec());
+    let form = SubmissionForm {
+        osu_version: "custom",
+        ..form
+    };
+    assert_eq!(registry.decrypt_submission(&form).unwrap().score, 38645);
+    assert!(registry.insert("short", b"too short").is_err());
+    assert!(registry.cipher("not a version").is_err());
+
+    // client versions that aren't dates work but aren't cached, dates only up
+    // to MAX_CACHED_VERSIONS
+    let len = registry.len();
+    registry.cipher("abcdefgh").unwrap();
+    registry.cipher("20211301").unwrap();
+    assert_eq!(registry.len(), len);
+    for year in 1000..1000 + MAX_CACHED_VERSIONS + 10 {
+        registry.cipher(&format!("{}0101", year)).unwrap();
+    }
+    assert_eq!(registry.len(), len + MAX_CACHED_VERSIONS - 1);
+}
+
+#[cfg(all(feature = "osu", feature = "json", feature = "toml"))]
+#[test]
+fn test_osu_key_registry_files() {
+    use crate::osu::KeyRegistry;
+
+    let mut registry = KeyRegistry::new();
+    registry
+        .load_json(r#"{ "json": "osu!-scoreburgr---------20210520" }"#)
+        .unwrap();
+    registry
+        .load_toml(r#"toml = "osu!-scoreburgr---------20210520""#)
+        .unwrap();
+    assert_eq!(registry.key("json").unwrap(), OSU_KEY.to_vec());
+    assert_eq!(registry.key("toml").unwrap(), OSU_KEY.to_vec());
+    assert!(registry.load_json("[]").is_err());
+
+    let path = std::env::temp_dir().join("simple_rijndael_key_registry.toml");
+    std::fs::write(&path, "file = \"osu!-scoreburgr---------20210520\"").unwrap();
+    registry.load_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(registry.key("file").unwrap(), OSU_KEY.to_vec());
+    assert!(registry.load_file("missing.toml").is_err());
+}
+
+fn unhex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_mcrypt() {
+    use crate::mcrypt::{self, Algorithm, Mode};
+
+    let fixtures = include_str!("../tests/fixtures/mcrypt.txt");
+    for line in fixtures.lines().filter(|line| !line.starts_with('#')) {
+        let fields: Vec<&str> = line.split(' ').collect();
+        let algorithm: Algorithm = fields[0].parse().unwrap();
+        let mode: Mode = fields[1].parse().unwrap();
+        let (key, iv) = (unhex(fields[2]), unhex(fields[3]));
+        let (plain, crypted) = (unhex(fields[4]), unhex(fields[5]));
+
+        let encrypted = mcrypt::encrypt(algorithm, &key, &plain, mode, &iv).unwrap();
+        assert_eq!(encrypted, crypted, "{}", line);
+        let mut decrypted = mcrypt::decrypt(algorithm, &key, &crypted, mode, &iv).unwrap();
+        if let Mode::Ecb | Mode::Cbc = mode {
+            // mcrypt does not remove the zero padding
+            assert!(decrypted[plain.len()..].iter().all(|b| *b == 0));
+            decrypted.truncate(plain.len());
+        }
+        assert_eq!(decrypted, plain, "{}", line);
+    }
+
+    // empty data is padded to one zero block
+    let key = [1; 16];
+    let zero_block = crate::rijndael::Rijndael::new(&key, 16)
+        .unwrap()
+        .encrypt(&[0; 16])
+        .unwrap();
+    for mode in [Mode::Ecb, Mode::Cbc] {
+        let encrypted = mcrypt::encrypt(Algorithm::Rijndael128, &key, b"", mode, &[0; 16]).unwrap();
+        assert_eq!(encrypted, zero_block);
+        let decrypted = mcrypt::decrypt(Algorithm::Rijndael128, &key, b"", mode, &[0; 16]).unwrap();
+        assert_eq!(decrypted.len(), 16);
+    }
+
+    // keys are zero padded to the next key size and cut at 32 bytes
+    for (short, padded) in [
+        (&b"short key"[..], b"short key\0\0\0\0\0\0\0".to_vec()),
+        (&[2; 40], vec![2; 32]),
+    ] {
+        assert_eq!(
+            mcrypt::encrypt(Algorithm::Rijndael192, short, b"data", Mode::Ecb, &[]).unwrap(),
+            mcrypt::encrypt(Algorithm::Rijndael192, &padded, b"data", Mode::Ecb, &[]).unwrap()
+        );
+    }
+
+    // IVs must be one block, except in ECB
+    for iv in [&[0; 15][..], &[0; 17], &[]] {
+        for mode in [Mode::Cbc, Mode::Cfb, Mode::Ofb, Mode::Ncfb, Mode::Nofb] {
+            assert!(matches!(
+                mcrypt::encrypt(Algorithm::Rijndael128, &key, b"data", mode, iv),
+                Err(crate::Errors::InvalidIvSize { .. })
+            ));
+            assert!(matches!(
+                mcrypt::decrypt(Algorithm::Rijndael128, &key, b"data", mode, iv),
+                Err(crate::Errors::InvalidIvSize { .. })
+            ));
+        }
+        assert!(mcrypt::encrypt(Algorithm::Rijndael128, &key, b"data", Mode::Ecb, iv).is_ok());
+    }
+    assert!("rijndael-512".parse::<Algorithm>().is_err());
+    assert!("ctr".parse::<Mode>().is_err());
+}
+
+#[cfg(feature = "dotnet")]
+#[test]
+fn test_dotnet() {
+    use crate::dotnet::{CipherMode, PaddingMode, Rfc2898DeriveBytes, RijndaelManaged};
+
+    let mut keys = std::collections::HashMap::new();
+    let fixtures = include_str!("../tests/fixtures/dotnet.txt");
+    for line in fixtures.lines().filter(|line| !line.starts_with('#')) {
+        let fields: Vec<&str> = line.split(' ').collect();
+        let block_size: usize = fields[0].parse().unwrap();
+        let mut r = RijndaelManaged {
+            block_size,
+            feedback_size: fields[2].parse().unwrap(),
+            mode: match fields[1] {
+                "CBC" => CipherMode::CBC,
+                "ECB" => CipherMode::ECB,
+                _ => CipherMode::CFB,
+            },
+            padding: match fields[3] {
+                "None" => PaddingMode::None,
+                "PKCS7" => PaddingMode::PKCS7,
+                "Zeros" => PaddingMode::Zeros,
+                "ANSIX923" => PaddingMode::ANSIX923,
+                _ => PaddingMode::ISO10126,
+            },
+            ..Default::default()
+        };
+        (r.key, r.iv) = keys
+            .entry(block_size)
+            .or_insert_with(|| {
+                let mut derive = Rfc2898DeriveBytes::new(b"password", b"saltsalt", 1000).unwrap();
+                r.derive(&mut derive, block_size).unwrap();
+                (r.key.clone(), r.iv.clone())
+            })
+            .clone();
+        assert_eq!((&r.key, &r.iv), (&unhex(fields[4]), &unhex(fields[5])));
+        let (plain, crypted) = (unhex(fields[6]), unhex(fields[7]));
+
+        let mut decrypted = r.decrypt(&crypted).unwrap();
+        if r.padding == PaddingMode::Zeros {
+            assert!(decrypted[plain.len()..].iter().all(|b| *b == 0));
+            decrypted.truncate(plain.len());
+        }
+        assert_eq!(decrypted, plain, "{}", line);
+        // ISO10126 pads with random bytes
+        if r.padding != PaddingMode::ISO10126 {
+            assert_eq!(r.encrypt(&plain).unwrap(), crypted, "{}", line);
+        } else {
+            assert_eq!(r.decrypt(&r.encrypt(&plain).unwrap()).unwrap(), plain);
+        }
+    }
+
+    // ISO10126 only fixes the last byte, the length
+    let mut r = RijndaelManaged {
+        padding: PaddingMode::ISO10126,
+        ..RijndaelManaged::new(&[1; 16], &[2; 16])
+    };
+    let crypted = r.encrypt(b"Rijndael").unwrap();
+    r.padding = PaddingMode::None;
+    let raw = r.decrypt(&crypted).unwrap();
+    assert_eq!((&raw[..8], raw.len(), raw[15]), (&b"Rijndael"[..], 16, 8));
+
+    // osu! stable encrypts scores with RijndaelManaged { BlockSize = 256, Padding = PKCS7 }
+    let r = RijndaelManaged {
+        block_size: 256,
+        ..RijndaelManaged::new(OSU_KEY, &OSU_IV)
+    };
+    assert_eq!(
+        r.encrypt(&OSU_DECRYPTED[..141]).unwrap(),
+        OSU_CRYPTED.to_vec()
+    );
+    assert_eq!(
+        r.decrypt(&OSU_CRYPTED).unwrap(),
+        OSU_DECRYPTED[..141].to_vec()
+    );
+    assert!(r.decrypt(&OSU_CRYPTED[..32]).is_err());
+
+    let mut derive = Rfc2898DeriveBytes::new(
+        b"passwordPASSWORDpassword",
+        b"saltSALTsaltSALTsaltSALTsaltSALTsalt",
+        4096,
+    )
+    .unwrap();
+    let mut derived = derive.get_bytes(10).unwrap();
+    derived.extend(derive.get_bytes(15).unwrap());
+    assert_eq!(
+        derived,
+        unhex("3d2eec4fe41c849b80c8d83662c0e44a8b291a964cf2f07038")
+    );
+    derive.reset();
+    assert_eq!(derive.get_bytes(25).unwrap(), derived);
+    let expected =
+        crate::kdf::pbkdf2(crate::kdf::Prf::HmacSha1, b"pw", b"saltsalt", 3, 100).unwrap();
+    let mut derive = Rfc2898DeriveBytes::new(b"pw", b"saltsalt", 3).unwrap();
+    let mut streamed = Vec::new();
+    for count in [0, 7, 13, 20, 1, 59] {
+        streamed.extend(derive.get_bytes(count).unwrap());
+    }
+    assert_eq!(streamed, expected);
+    assert!(Rfc2898DeriveBytes::new(b"password", b"salt", 1000).is_err());
+}
+
+#[test]
+fn test_ansix923_iso10126() {
+    use crate::paddings::{AnsiX923Padding, Iso10126Padding, Padding};
+
+    let p = AnsiX923Padding::new(8).unwrap();
+    assert_eq!(p.encode(b"abc".to_vec()), b"abc\0\0\0\0\x05".to_vec());
+    assert_eq!(p.encode(vec![1; 8]).len(), 16);
+    assert_eq!(p.decode(b"abc\0\0\0\0\x05".to_vec()).unwrap(), b"abc");
+    assert!(p.decode(b"abc\0\0\x01\0\x05".to_vec()).is_err());
+    assert!(p.decode(b"abcdefg\x09".to_vec()).is_err());
+
+    let p = Iso10126Padding::new(8).unwrap();
+    let padded = p.encode(b"abc".to_vec());
+    assert_eq!((padded.len(), padded[7]), (8, 5));
+    assert_eq!(p.decode(padded).unwrap(), b"abc");
+    assert!(p.decode(b"abcdefg\0".to_vec()).is_err());
+    assert!(p.decode(vec![]).is_err());
+}
+
+#[test]
+fn test_padding_kind() {
+    use crate::paddings::{AnsiX923Padding, Padding, PaddingKind, Pkcs7Padding};
+    use crate::Errors;
+
+    // chosen per partner at runtime
+    for (name, block_size) in [("zero", 32), ("pkcs7", 16), ("ansix923", 24)] {
+        let padding = PaddingKind::from_name(name, block_size).unwrap();
+        assert_eq!((padding.name(), padding.size()), (name, block_size));
+        let cbc = RijndaelCbc::with_padding(OSU_KEY, padding).unwrap();
+        let encrypted = cbc
+            .encrypt(&OSU_IV[..block_size], b"runtime padding".to_vec())
+            .unwrap();
+        let decrypted = cbc.decrypt(&OSU_IV[..block_size], encrypted).unwrap();
+        assert_eq!(&decrypted[..15], b"runtime padding");
+    }
+
+    let kind = RijndaelCbc::with_padding(OSU_KEY, PaddingKind::from_name("zero", 32).unwrap())
+        .unwrap()
+        .encrypt(&OSU_IV, OSU_DECRYPTED.to_vec())
+        .unwrap();
+    assert_eq!(kind, OSU_CRYPTED.to_vec());
+    let kind = RijndaelCbc::<PaddingKind>::new(b"128 bit key test", 16).unwrap();
+    let generic = RijndaelCbc::<Pkcs7Padding>::new(b"128 bit key test", 16).unwrap();
+    assert_eq!(kind.padding.name(), "pkcs7");
+    assert_eq!(
+        kind.encrypt(b"128 bit iv_ test", b"default".to_vec()),
+        generic.encrypt(b"128 bit iv_ test", b"default".to_vec())
+    );
+
+    let paddings: Vec<Box<dyn Padding>> = vec![
+        Box::new(Pkcs7Padding::new(8).unwrap()),
+        Box::new(AnsiX923Padding::new(8).unwrap()),
+        Box::new(PaddingKind::from_name("iso10126", 8).unwrap()),
+    ];
+    for padding in &paddings {
+        let encoded = padding.encode(b"abc".to_vec());
+        assert_eq!((encoded.len(), encoded[7]), (8, 5));
+        assert_eq!(padding.decode(encoded).unwrap(), b"abc");
+    }
+    let boxed: [Box<dyn Padding>; 2] = [
+        Box::new(AnsiX923Padding::new(16).unwrap()),
+        Box::new(PaddingKind::from_name("zero", 16).unwrap()),
+    ];
+    for padding in boxed {
+        let cbc = RijndaelCbc::with_padding(b"128 bit key test", padding).unwrap();
+        let encrypted = cbc.encrypt(b"128 bit iv_ test", b"boxed".to_vec()).unwrap();
+        let decrypted = cbc.decrypt(b"128 bit iv_ test", encrypted).unwrap();
+        assert_eq!(&decrypted[..5], b"boxed");
+    }
+    let boxed = RijndaelCbc::<Box<dyn Padding + Send + Sync>>::new(b"128 bit key test", 16)
+        .unwrap()
+        .encrypt(b"128 bit iv_ test", b"default".to_vec());
+    assert_eq!(
+        boxed,
+        generic.encrypt(b"128 bit iv_ test", b"default".to_vec())
+    );
+
+    assert_eq!(
+        PaddingKind::from_name("pkcs5", 16).unwrap_err(),
+        Errors::InvalidPadding
+    );
+    assert!(
+        RijndaelCbc::with_padding(OSU_KEY, PaddingKind::from_name("zero", 20).unwrap()).is_err()
+    );
+
+    // the padding length has to fit in one byte
+    for block_size in [0, 256, 1000] {
+        assert_eq!(
+            PaddingKind::from_name("pkcs7", block_size).unwrap_err(),
+            Errors::InvalidBlockSize {
+                expected: crate::Size::Between(1, 255),
+                actual: block_size
+            }
+        );
+        assert!(AnsiX923Padding::new(block_size).is_err());
+    }
+    let padded = Pkcs7Padding::new(255).unwrap().encode(vec![]);
+    assert_eq!(padded, vec![255; 255]);
+}
+
+#[test]
+fn test_cipher() {
+    use crate::cipher::{Algorithm, Cipher, CipherSpec};
+    use crate::modes::Mode;
+    use crate::paddings::{PaddingKind, Pkcs7Padding};
+    use crate::{Errors, Size};
+
+    let osu = Cipher::new("rijndael-256-cbc/zero", OSU_KEY).unwrap();
+    assert_eq!(osu.iv_size(), 32);
+    assert_eq!(
+        osu.encrypt(&OSU_IV, &OSU_DECRYPTED).unwrap(),
+        OSU_CRYPTED.to_vec()
+    );
+    assert_eq!(
+        osu.decrypt(&OSU_IV, &OSU_CRYPTED).unwrap(),
+        OSU_DECRYPTED.to_vec()
+    );
+
+    let test_data = b"This string is used to test Pkcs7Padding";
+    let (key, iv) = (b"128 bit key test", b"128 bit iv_ test");
+    let aes = Cipher::new("AES-128-CBC", key).unwrap();
+    assert_eq!(aes.spec().to_string(), "aes-128-cbc/pkcs7");
+    assert_eq!(
+        aes.encrypt(iv, test_data).unwrap(),
+        RijndaelCbc::<Pkcs7Padding>::new(key, 16)
+            .unwrap()
+            .encrypt(iv, test_data.to_vec())
+            .unwrap()
+    );
+
+    for (id, key_size) in [
+        ("rijndael-192-ecb/ansix923", 16),
+        ("rijndael-128-cbc/iso10126", 32),
+        ("aes-192-cfb/none", 24),
+        ("aes-256-ofb/none", 32),
+        ("rijndael-256-ofb/none", 24),
+    ] {
+        let spec: CipherSpec = id.parse().unwrap();
+        assert_eq!(spec.to_string(), id);
+        assert_eq!(spec.to_string().parse::<CipherSpec>().unwrap(), spec);
+        let cipher = Cipher::from_spec(spec, &vec![9; key_size]).unwrap();
+        let iv = vec![3; cipher.iv_size()];
+        let encrypted = cipher.encrypt(&iv, test_data).unwrap();
+        assert_eq!(cipher.decrypt(&iv, &encrypted).unwrap(), test_data);
+    }
+    let spec: CipherSpec = "aes-256-ofb".parse().unwrap();
+    assert_eq!(
+        (spec.algorithm, spec.mode, spec.padding),
+        (Algorithm::Aes { key_size: 32 }, Mode::Ofb, None)
+    );
+
+    let parse = |id: &str| id.parse::<CipherSpec>().unwrap_err();
+    assert_eq!(parse("des-128-cbc"), Errors::InvalidAlgorithm);
+    assert_eq!(parse("aes-512-cbc"), Errors::InvalidAlgorithm);
+    assert_eq!(parse("aes-128"), Errors::InvalidAlgorithm);
+    assert_eq!(parse(""), Errors::InvalidAlgorithm);
+    assert_eq!(parse("aes-128-ctr"), Errors::InvalidMode);
+    assert_eq!(parse("aes-128-cbc-hmac"), Errors::InvalidMode);
+    assert_eq!(parse("aes-128-cbc/pkcs5"), Errors::InvalidPadding);
+    assert_eq!(parse("aes-128-ofb/pkcs7"), Errors::InvalidPadding);
+    assert!(matches!(
+        Cipher::new("aes-128-cbc", OSU_KEY).unwrap_err(),
+        Errors::InvalidKeySize { .. }
+    ));
+    let ecb = Cipher::new("aes-128-ecb", key).unwrap();
+    assert!(matches!(
+        ecb.encrypt(iv, test_data).unwrap_err(),
+        Errors::InvalidIvSize { .. }
+    ));
+    assert!(ecb.decrypt(&[], &[0; 15]).is_err());
+
+    // specs built by hand are checked too
+    let mut spec: CipherSpec = "rijndael-256-cbc/zero".parse().unwrap();
+    spec.padding = Some(PaddingKind::from_name("zero", 16).unwrap());
+    assert_eq!(
+        Cipher::from_spec(spec.clone(), OSU_KEY).unwrap_err(),
+        Errors::InvalidBlockSize {
+            expected: Size::Exactly(32),
+            actual: 16
+        }
+    );
+    spec.padding = Some(PaddingKind::from_name("zero", 32).unwrap());
+    spec.mode = Mode::Ofb;
+    assert_eq!(
+        Cipher::from_spec(spec, OSU_KEY).unwrap_err(),
+        Errors::InvalidPadding
+    );
+}
+
+#[cfg(feature = "openssl")]
+#[test]
+fn test_openssl_enc() {
+    use crate::openssl::{
+        parse_header, KeyDerivation, MessageDigest, OpensslEnc, DEFAULT_ITERATIONS,
+    };
+
+    let plain = include_bytes!("../tests/fixtures/openssl/plain.txt").to_vec();
+    let fixtures: [(OpensslEnc, &[u8]); 4] = [
+        (
+            OpensslEnc::new(32, KeyDerivation::BytesToKey(MessageDigest::Md5)),
+            include_bytes!("../tests/fixtures/openssl/aes-256-cbc-md5.enc"),
+        ),
+        (
+            OpensslEnc::new(16, KeyDerivation::BytesToKey(MessageDigest::Md5)),
+            include_bytes!("../tests/fixtures/openssl/aes-128-cbc-md5.enc"),
+        ),
+        (
+            OpensslEnc::new(32, KeyDerivation::BytesToKey(MessageDigest::Sha256)),
+            include_bytes!("../tests/fixtures/openssl/aes-256-cbc-sha256.enc"),
+        ),
+        (
+            OpensslEnc::new(32, KeyDerivation::Pbkdf2(10000)),
+            include_bytes!("../tests/fixtures/openssl/aes-256-cbc-pbkdf2.enc"),
+        ),
+    ];
+    for (enc, file) in fixtures {
+        assert_eq!(enc.decrypt(b"secret", file).unwrap(), plain);
+        let salt = parse_header(file).unwrap().0.try_into().unwrap();
+        assert_eq!(enc.encrypt(b"secret", &salt, plain.clone()).unwrap(), file);
+        assert_ne!(enc.decrypt(b"wrong", file).ok(), Some(plain.clone()));
+    }
+
+    let enc = OpensslEnc::new(24, KeyDerivation::Pbkdf2(DEFAULT_ITERATIONS));
+    let file = include_str!("../tests/fixtures/openssl/aes-192-cbc-pbkdf2.b64");
+    assert_eq!(enc.decrypt_base64(b"secret", file).unwrap(), plain);
+    let salt = parse_header(
+        &base64::Engine::decode(
+            &base64::engine::general_purpose::STANDARD,
+            file.replace('\n', ""),
+        )
+        .unwrap(),
+    )
+    .unwrap()
+    .0
+    .try_into()
+    .unwrap();
+    assert_eq!(
+        enc.encrypt_base64(b"secret", &salt, plain.clone()).unwrap(),
+        file
+    );
+
+    assert!(enc.decrypt(b"secret", b"Salted__").is_err());
+    assert!(enc.decrypt(b"secret", b"Salted__12345678").is_err());
+    assert!(enc.decrypt(b"secret", &[0; 32]).is_err());
+}
+
+#[cfg(feature = "kdf")]
+#[test]
+fn test_kdf() {
+    use crate::kdf::{self, KeyMaterial, Prf};
+    use crate::paddings::Pkcs7Padding;
+
+    assert_eq!(
+        kdf::pbkdf2(Prf::HmacSha256, b"password", b"salt", 1, 32).unwrap(),
+        unhex("120fb6cffcf8b32c43e7225256c4f837a86548c92ccc35480805987cb70be17b")
+    );
+    assert_eq!(
+        kdf::pbkdf2(Prf::HmacSha1, b"password", b"salt", 2, 20).unwrap(),
+        unhex("ea6c014dc72d6f8ccd1ed92ace1d41f0d8de8957")
+    );
+    assert!(kdf::pbkdf2(Prf::HmacSha256, b"password", b"salt", 0, 32).is_err());
+    // RFC 5869 test case 1
+    assert_eq!(
+        kdf::hkdf_sha256(
+            &[0x0b; 22],
+            Some(&unhex("000102030405060708090a0b0c")),
+            &unhex("f0f1f2f3f4f5f6f7f8f9"),
+            42
+        )
+        .unwrap(),
+        unhex(
+            "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865"
+        )
+    );
+    assert!(kdf::hkdf_sha256(b"ikm", None, b"", 255 * 32 + 1).is_err());
+
+    let material = KeyMaterial::hkdf_sha256(b"ikm", None, b"info", 32, 24).unwrap();
+    assert_eq!((material.key.len(), material.iv.len()), (24, 32));
+    assert!(KeyMaterial::pbkdf2(Prf::HmacSha256, b"pw", b"salt", 1, 20, 16).is_err());
+    assert!(KeyMaterial::pbkdf2(Prf::HmacSha256, b"pw", b"salt", 1, 16, 20).is_err());
+
+    let (r, iv) = RijndaelCbc::<Pkcs7Padding>::from_password(b"pw", b"salt", 1000, 32, 16).unwrap();
+    let material = KeyMaterial::pbkdf2(Prf::HmacSha256, b"pw", b"salt", 1000, 32, 16).unwrap();
+    assert_eq!(iv, material.iv);
+    let encrypted = r.encrypt(&iv, b"from password".to_vec()).unwrap();
+    assert_eq!(
+        RijndaelCbc::<Pkcs7Padding>::new(&material.key, 32)
+            .unwrap()
+            .decrypt(&iv, encrypted)
+            .unwrap(),
+        b"from password"
+    );
+}
+
+#[cfg(feature = "envelope")]
+#[test]
+fn test_envelope() {
+    use crate::envelope::{self, Envelope, KdfParams, Params};
+    use crate::kdf::Prf;
+    use crate::modes::Mode;
+    use crate::paddings::PaddingKind;
+    use crate::Errors;
+
+    let key = [7; 24];
+    let data = b"self-describing envelope".to_vec();
+    for (mode, padding) in [
+        (Mode::Ecb, Some("pkcs7")),
+        (Mode::Cbc, Some("ansix923")),
+        (Mode::Cbc, Some("iso10126")),
+        (Mode::Cfb, None),
+        (Mode::Ofb, None),
+    ] {
+        for block_size in [16, 24, 32] {
+            for mac in [false, true] {
+                let params = Params {
+                    mode,
+                    block_size,
+                    key_size: 24,
+                    padding: padding.map(|name| PaddingKind::from_name(name, block_size).unwrap()),
+                    kdf: None,
+                    mac,
+                };
+                let iv = match mode {
+                    Mode::Ecb => vec![],
+                    _ => vec![1; block_size],
+                };
+                let sealed = envelope::seal(&params, &key, &iv, &data).unwrap();
+                assert_eq!(Envelope::parse(&sealed).unwrap().params, params);
+                assert_eq!(envelope::open_unauthenticated(&key, &sealed).unwrap(), data);
+                match mac {
+                    true => assert_eq!(envelope::open(&key, &sealed).unwrap(), data),
+                    false => assert!(matches!(
+                        envelope::open(&key, &sealed),
+                        Err(Errors::AuthenticationFailed)
+                    )),
+                }
+            }
+        }
+    }
+
+    let params = Params {
+        kdf: Some(KdfParams {
+            prf: Prf::HmacSha256,
+            iterations: 1000,
+            salt: b"envelope salt".to_vec(),
+        }),
+        ..Default::default()
+    };
+    let sealed = envelope::seal(&params, b"password", &[2; 16], &data).unwrap();
+    assert_eq!(envelope::open(b"password", &sealed).unwrap(), data);
+    assert!(matches!(
+        envelope::open(b"passw0rd", &sealed),
+        Err(Errors::AuthenticationFailed)
+    ));
+
+    // tampering with the header or the ciphertext fails the MAC
+    for i in [6, 40, sealed.len() - 40, sealed.len() - 1] {
+        let mut tampered = sealed.clone();
+        tampered[i] ^= 1;
+        assert!(envelope::open(b"password", &tampered).is_err());
+    }
+
+    // stripping the tag doesn't bypass it
+    let mut stripped = sealed[..sealed.len() - 32].to_vec();
+    stripped[9] &= !envelope::FLAG_MAC;
+    assert!(matches!(
+        envelope::open(b"password", &stripped),
+        Err(Errors::AuthenticationFailed)
+    ));
+
+    // PBKDF2 iterations are capped before deriving
+    let mut expensive = sealed.clone();
+    expensive[27..31].copy_from_slice(&(envelope::MAX_ITERATIONS + 1).to_be_bytes());
+    assert!(matches!(
+        Envelope::parse(&expensive),
+        Err(Errors::InvalidKdfParameters)
+    ));
+    let mut params = params;
+    params.kdf.as_mut().unwrap().iterations = envelope::MAX_ITERATIONS + 1;
+    assert!(matches!(
+        envelope::seal(&params, b"password", &[2; 16], &data),
+        Err(Errors::InvalidKdfParameters)
+    ));
+
+    let sealed = envelope::seal(&Params::default(), &[3; 32], &[4; 16], &data).unwrap();
+    assert!(matches!(
+        envelope::open(&[3; 16], &sealed),
+        Err(Errors::InvalidKeySize { .. })
+    ));
+    let reject = |i: usize, value: u8| {
+        let mut broken = sealed.clone();
+        broken[i] = value;
+        envelope::open(&[3; 32], &broken).unwrap_err()
+    };
+    assert!(matches!(reject(0, b'X'), Errors::InvalidHeader));
+    assert!(matches!(reject(4, 1), Errors::InvalidVersion));
+    assert!(matches!(reject(5, 9), Errors::InvalidMode));
+    assert!(matches!(reject(6, 20), Errors::InvalidBlockSize { .. }));
+    assert!(matches!(reject(7, 17), Errors::InvalidKeySize { .. }));
+    assert!(matches!(reject(8, 9), Errors::InvalidPadding));
+    assert!(matches!(reject(9, 4), Errors::InvalidHeader));
+    for size in 0..sealed.len() {
+        assert!(Envelope::parse(&sealed[..size]).is_err());
+    }
+    let mut trailing = sealed.clone();
+    trailing.push(0);
+    assert!(matches!(
+        Envelope::parse(&trailing),
+        Err(Errors::InvalidDataSize { .. })
+    ));
+
+    assert!(matches!(
+        envelope::seal(&Params::default(), &[3; 32], &[4; 8], &data),
+        Err(Errors::InvalidIvSize { .. })
+    ));
+    let params = Params {
+        mode: Mode::Ofb,
+        ..Default::default()
+    };
+    assert!(matches!(
+        envelope::seal(&params, &[3; 32], &[4; 16], &data),
+        Err(Errors::InvalidPadding)
+    ));
+    let params = Params {
+        block_size: 32,
+        ..Default::default()
+    };
+    assert!(matches!(
+        envelope::seal(&params, &[3; 32], &[4; 32], &data),
+        Err(Errors::InvalidBlockSize { .. })
+    ));
+}
+
+#[cfg(feature = "hmac")]
+#[test]
+fn test_cbc_hmac() {
+    use crate::cbc_hmac::RijndaelCbcHmac;
+    use crate::paddings::Pkcs7Padding;
+    use crate::Errors;
+
+    let master_key: Vec<u8> = (0..32).collect();
+    let iv: Vec<u8> = (0x40..0x50).collect();
+    let cipher = RijndaelCbcHmac::<Pkcs7Padding>::new(&master_key, 16, 16).unwrap();
+    let sealed = cipher.encrypt(&iv, b"encrypt then mac".to_vec()).unwrap();
+    // AES-128-CBC and HMAC-SHA256 over IV || C || AL from Python's `cryptography`
+    assert_eq!(
+        sealed,
+        unhex("6977e08d7b9f128179a6d1e29d5dce3d9ccac393fdc68c4231297f15739d0a50182b28610f168a7e48218eee6dd073d5")
+    );
+    assert_eq!(
+        cipher.decrypt(&iv, sealed.clone()).unwrap(),
+        b"encrypt then mac"
+    );
+    for i in 0..sealed.len() {
+        let mut tampered = sealed.clone();
+        tampered[i] ^= 0x80;
+        assert!(matches!(
+            cipher.decrypt(&iv, tampered),
+            Err(Errors::AuthenticationFailed)
+        ));
+    }
+    let mut other_iv = iv.clone();
+    other_iv[0] ^= 1;
+    assert!(matches!(
+        cipher.decrypt(&other_iv, sealed.clone()),
+        Err(Errors::AuthenticationFailed)
+    ));
+    assert!(matches!(
+        cipher.decrypt(&iv, sealed[..20].to_vec()),
+        Err(Errors::InvalidDataSize { .. })
+    ));
+    assert!(matches!(
+        cipher.decrypt(&iv[..8], sealed),
+        Err(Errors::InvalidIvSize { .. })
+    ));
+
+    let cipher = RijndaelCbcHmac::<Pkcs7Padding>::new(&[9; 64], 32, 32).unwrap();
+    let sealed = cipher.encrypt(&[1; 32], vec![2; 100]).unwrap();
+    assert_eq!(sealed.len(), 128 + 32);
+    assert_eq!(cipher.decrypt(&[1; 32], sealed).unwrap(), vec![2; 100]);
+
+    assert!(matches!(
+        RijndaelCbcHmac::<Pkcs7Padding>::new(&[0; 32], 16, 8),
+        Err(Errors::InvalidTagSize { .. })
+    ));
+    assert!(matches!(
+        RijndaelCbcHmac::<Pkcs7Padding>::new(&[0; 40], 16, 16),
+        Err(Errors::InvalidKeySize { .. })
+    ));
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn test_random_iv() {
+    use crate::paddings::Pkcs7Padding;
+    use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+
+    let cbc = RijndaelCbc::<Pkcs7Padding>::new(&[5; 32], 32).unwrap();
+    let mut rng = ChaCha20Rng::seed_from_u64(42);
+    let first = cbc
+        .encrypt_with_random_iv(&mut rng, b"random iv".to_vec())
+        .unwrap();
+    let second = cbc
+        .encrypt_with_random_iv(&mut rng, b"random iv".to_vec())
+        .unwrap();
+    assert_eq!(first.len(), 64);
+    assert_ne!(first[..32], second[..32]);
+    assert_eq!(cbc.decrypt_prefixed_iv(&first).unwrap(), b"random iv");
+    assert_eq!(cbc.decrypt_prefixed_iv(&second).unwrap(), b"random iv");
+
+    // the same seed gives the same IV
+    let again = cbc
+        .encrypt_with_random_iv(&mut ChaCha20Rng::seed_from_u64(42), b"random iv".to_vec())
+        .unwrap();
+    assert_eq!(first, again);
+    assert_eq!(
+        cbc.decrypt(&first[..32], first[32..].to_vec()).unwrap(),
+        b"random iv"
+    );
+    assert!(cbc.decrypt_prefixed_iv(&first[..32]).is_err());
+}
+
+#[cfg(all(feature = "serde", feature = "json", feature = "toml"))]
+#[test]
+fn test_cipher_config() {
+    use crate::config::{CipherConfig, EncodedBytes, Mode};
+    use crate::Errors;
+
+    let config: CipherConfig = toml::from_str(
+        r#"
+        mode = "cbc"
+        block_size = 32
+        key_size = 32
+        padding = "zero"
+        key = "base64:b3N1IS1zY29yZWJ1cmdyLS0tLS0tLS0tMjAyMTA1MjA="
+        iv = "f07c1a9a1bba62aa5fbed5670d80273bd9544490ad5d758421d5609ae4e7c5a2"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.key, EncodedBytes::Base64(OSU_KEY.to_vec()));
+    assert_eq!(config.iv, Some(EncodedBytes::Hex(OSU_IV.to_vec())));
+    let cipher = config.build().unwrap();
+    assert_eq!(
+        cipher.encrypt(&OSU_DECRYPTED).unwrap(),
+        OSU_CRYPTED.to_vec()
+    );
+    assert_eq!(
+        cipher.decrypt(&OSU_CRYPTED).unwrap(),
+        OSU_DECRYPTED.to_vec()
+    );
+
+    let toml = toml::to_string(&config).unwrap();
+    assert!(toml.contains("iv = \"hex:f07c1a9a"));
+    assert_eq!(toml::from_str::<CipherConfig>(&toml).unwrap(), config);
+    let json = serde_json::to_string(&config).unwrap();
+    assert_eq!(serde_json::from_str::<CipherConfig>(&json).unwrap(), config);
+
+    let data = b"cipher settings from a config file";
+    for (mode, padding, iv) in [
+        (Mode::Ecb, "pkcs7", None),
+        (
+            Mode::Cbc,
+            "ansix923",
+            Some(EncodedBytes::Base64(vec![1; 16])),
+        ),
+        (Mode::Cbc, "iso10126", Some(EncodedBytes::Hex(vec![2; 16]))),
+        (Mode::Cfb, "none", Some(EncodedBytes::Hex(vec![3; 16]))),
+        (Mode::Ofb, "none", Some(EncodedBytes::Base64(vec![4; 16]))),
+    ] {
+        let config = CipherConfig {
+            mode,
+            block_size: 16,
+            key_size: 24,
+            padding: padding.into(),
+            key: EncodedBytes::Hex(vec![5; 24]),
+            iv,
+        };
+        let json = serde_json::to_string(&config).unwrap();
+        let parsed: CipherConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, config);
+        let encrypted = config.build().unwrap().encrypt(data).unwrap();
+        assert_eq!(parsed.build().unwrap().decrypt(&encrypted).unwrap(), data);
+    }
+
+    let invalid = |f: fn(&mut CipherConfig)| {
+        let mut config = config.clone();
+        f(&mut config);
+        config.build().unwrap_err()
+    };
+    assert_eq!(
+        invalid(|c| c.padding = "pkcs5".into()),
+        Errors::InvalidPadding
+    );
+    assert_eq!(invalid(|c| c.mode = Mode::Ofb), Errors::InvalidPadding);
+    assert!(matches!(
+        invalid(|c| c.key_size = 16),
+        Errors::InvalidKeySize { .. }
+    ));
+    assert!(matches!(
+        invalid(|c| c.block_size = 20),
+        Errors::InvalidBlockSize { .. }
+    ));
+    assert!(matches!(
+        invalid(|c| c.iv = None),
+        Errors::InvalidIvSize { .. }
+    ));
+    assert!(matches!(
+        invalid(|c| c.mode = Mode::Ecb),
+        Errors::InvalidIvSize { .. }
+    ));
+    assert!(serde_json::from_str::<CipherConfig>(&json.replace("hex:", "hex:zz")).is_err());
+    assert!(serde_json::from_str::<CipherConfig>(&json.replace("\"mode\"", "\"mod\"")).is_err());
+    assert!(serde_json::from_str::<CipherConfig>(&json.replace("cbc", "ctr")).is_err());
+}
+
+#[test]
+fn test_typed_sizes() {
+    use crate::paddings::Pkcs7Padding;
+    use crate::rijndael::Rijndael;
+    use crate::types::{Block, Iv, Key};
+    use crate::Errors;
+
+    assert!(Key::new(&[0; 24]).is_ok());
+    assert!(matches!(
+        Key::new(&[0; 20]),
+        Err(Errors::InvalidKeySize { .. })
+    ));
+    assert_eq!(Iv::new(&[1; 32], 32).unwrap().len(), 32);
+    assert!(matches!(
+        Iv::new(&[1; 16], 32),
+        Err(Errors::InvalidIvSize { .. })
+    ));
+    assert!(matches!(
+        Iv::new(&[1; 20], 20),
+        Err(Errors::InvalidBlockSize { .. })
+    ));
+    assert!(Block::new(&[2; 24], 24).is_ok());
+    assert!(matches!(
+        Block::new(&[2; 23], 24),
+        Err(Errors::InvalidBlockSize { .. })
+    ));
+
+    let r = Rijndael::new(&[3; 16], 16).unwrap();
+    assert!(matches!(
+        r.encrypt(&[0; 15]),
+        Err(Errors::InvalidBlockSize { .. })
+    ));
+    assert!(matches!(
+        r.decrypt(&[0; 17]),
+        Err(Errors::InvalidBlockSize { .. })
+    ));
+
+    let cbc = Arc::new(RijndaelCbc::<Pkcs7Padding>::new(&[3; 16], 16).unwrap());
+    let encrypted = cbc.encrypt(&[4; 16], b"typed".to_vec()).unwrap();
+    for iv in [&[4; 8][..], &[4; 32][..], &[][..]] {
+        assert!(matches!(
+            cbc.encrypt(iv, b"typed".to_vec()),
+            Err(Errors::InvalidIvSize { .. })
+        ));
+        assert!(matches!(
+            cbc.decrypt(iv, encrypted.clone()),
+            Err(Errors::InvalidIvSize { .. })
+        ));
+        assert!(matches!(
+            cbc.encryptor(iv),
+            Err(Errors::InvalidIvSize { .. })
+        ));
+        assert!(matches!(
+            cbc.decryptor(iv),
+            Err(Errors::InvalidIvSize { .. })
+        ));
+    }
+
+    // checked IVs are taken as is, but still have to match the block size
+    let iv = Iv::new(&[4; 16], 16).unwrap();
+    assert_eq!(cbc.encrypt(iv, b"typed".to_vec()).unwrap(), encrypted);
+    assert_eq!(cbc.decrypt(iv, encrypted.clone()).unwrap(), b"typed");
+    let iv = Iv::new(&[4; 32], 32).unwrap();
+    assert!(matches!(
+        cbc.encrypt(iv, b"typed".to_vec()),
+        Err(Errors::InvalidIvSize { .. })
+    ));
+}
+
+/// xorshift64*, enough to generate arbitrary inputs without a dependency.
+struct Arbitrary(u64);
+
+impl Arbitrary {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545f4914f6cdd1d)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn bytes(&mut self, max: usize) -> Vec<u8> {
+        let size = self.below(max + 1);
+        (0..size).map(|_| self.next() as u8).collect()
+    }
+
+    /// Mostly valid sizes, sometimes anything.
+    fn size(&mut self) -> usize {
+        match self.below(4) {
+            0 => self.below(40),
+            _ => crate::constants::VALID[self.below(3)],
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_errors_display() {
+    use crate::{Errors, Size};
+
+    let err = RijndaelCbc::<ZeroPadding>::new(&[0; 16], 16)
+        .unwrap()
+        .encrypt(&[0; 8], vec![])
+        .unwrap_err();
+    assert_eq!(
+        err,
+        Errors::InvalidIvSize {
+            expected: Size::Exactly(16),
+            actual: 8
+        }
+    );
+    assert_eq!(err.to_string(), "Invalid IV Size: expected 16 bytes, got 8");
+    assert_eq!(
+        RijndaelCbc::<ZeroPadding>::new(&[0; 20], 16)
+            .unwrap_err()
+            .to_string(),
+        "Invalid Key Size: expected 16, 24 or 32 bytes, got 20"
+    );
+    assert_eq!(
+        Errors::InvalidDataSize {
+            expected: Size::MultipleOf(32),
+            actual: 33
+        }
+        .to_string(),
+        "Invalid Data Size: expected a multiple of 32 bytes, got 33"
+    );
+    assert_eq!(Errors::InvalidPadding.to_string(), "Invalid Padding");
+}
+
+#[test]
+fn test_no_panics() {
+    use crate::paddings::{AnsiX923Padding, Iso10126Padding, Padding, Pkcs7Padding};
+    use crate::{mcrypt, modes, rijndael::Rijndael};
+
+    fn cbc<P: Padding>(rng: &mut Arbitrary) {
+        let Ok(cbc) = RijndaelCbc::<P>::new(&rng.bytes(40), rng.size()).map(Arc::new) else {
+            return;
+        };
+        let iv = rng.bytes(40);
+        let data = rng.bytes(100);
+        let _ = cbc.encrypt(&iv, data.clone());
+        let _ = cbc.decrypt(&iv, data.clone());
+        let _ = cbc.decrypt_prefixed_iv(&data);
+        let mut out = Vec::new();
+        if let (Ok(mut encryptor), Ok(mut decryptor)) = (cbc.encryptor(&iv), cbc.decryptor(&iv)) {
+            for chunk in data.chunks(rng.below(20) + 1) {
+                let _ = encryptor.update(chunk, &mut out);
+                let _ = decryptor.update(chunk, &mut out);
+            }
+            let _ = encryptor.finalize(&mut out);
+            let _ = decryptor.finalize(&mut out);
+        }
+        if let Ok(padding) = P::new(rng.below(300)) {
+            let _ = padding.decode(padding.encode(data.clone()));
+            let _ = padding.decode(data);
+        }
+    }
+
+    let mut rng = Arbitrary(0x5eed);
+    for _ in 0..2000 {
+        if let Ok(r) = Rijndael::new(&rng.bytes(40), rng.size()) {
+            let data = rng.bytes(80);
+            let iv = rng.bytes(40);
+            let _ = r.encrypt(&data);
+            let _ = r.decrypt(&data);
+            let _ = modes::ecb_encrypt(&r, &data);
+            let _ = modes::ecb_decrypt(&r, &data);
+            let _ = modes::cbc_encrypt(&r, &iv, &data);
+            let _ = modes::cbc_decrypt(&r, &iv, &data);
+            let segment_size = rng.below(40);
+            let _ = modes::cfb_encrypt(&r, &iv, segment_size, &data);
+            let _ = modes::cfb_decrypt(&r, &iv, segment_size, &data);
+            let _ = modes::ofb(&r, &iv, segment_size, &data);
+        }
+        cbc::<ZeroPadding>(&mut rng);
+        cbc::<Pkcs7Padding>(&mut rng);
+        cbc::<AnsiX923Padding>(&mut rng);
+        cbc::<Iso10126Padding>(&mut rng);
+
+        let algorithm = [
+            mcrypt::Algorithm::Rijndael128,
+            mcrypt::Algorithm::Rijndael192,
+            mcrypt::Algorithm::Rijndael256,
+        ][rng.below(3)];
+        let mode = [
+            mcrypt::Mode::Ecb,
+            mcrypt::Mode::Cbc,
+            mcrypt::Mode::Cfb,
+            mcrypt::Mode::Ofb,
+            mcrypt::Mode::Ncfb,
+            mcrypt::Mode::Nofb,
+        ][rng.below(6)];
+        let (key, data, iv) = (rng.bytes(40), rng.bytes(80), rng.bytes(40));
+        let _ = mcrypt::encrypt(algorithm, &key, &data, mode, &iv);
+        let _ = mcrypt::decrypt(algorithm, &key, &data, mode, &iv);
+
+        #[cfg(feature = "hmac")]
+        if let Ok(cipher) = crate::cbc_hmac::RijndaelCbcHmac::<Pkcs7Padding>::new(
+            &rng.bytes(70),
+            rng.size(),
+            rng.below(40),
+        ) {
+            let iv = rng.bytes(40);
+            let _ = cipher.encrypt(&iv, rng.bytes(80));
+            let _ = cipher.decrypt(&iv, rng.bytes(100));
+        }
+
+        #[cfg(feature = "dotnet")]
+        {
+            use crate::dotnet::{CipherMode, PaddingMode, RijndaelManaged};
+            let managed = RijndaelManaged {
+                key: rng.bytes(40),
+                iv: rng.bytes(40),
+                block_size: rng.size() * 8 + [0, 0, 0, 3][rng.below(4)],
+                feedback_size: rng.below(40) * 8,
+                mode: [CipherMode::CBC, CipherMode::ECB, CipherMode::CFB][rng.below(3)],
+                padding: [
+                    PaddingMode::None,
+                    PaddingMode::PKCS7,
+                    PaddingMode::Zeros,
+                    PaddingMode::ANSIX923,
+                    PaddingMode::ISO10126,
+                ][rng.below(5)],
+            };
+            let data = rng.bytes(80);
+            let _ = managed.encrypt(&data);
+            let _ = managed.decrypt(&data);
+        }
+
+        #[cfg(feature = "openssl")]
+        {
+            use crate::openssl::{KeyDerivation, MessageDigest, OpensslEnc};
+            let mut data = rng.bytes(80);
+            if rng.below(2) == 0 {
+                data.splice(..0, crate::openssl::MAGIC.iter().copied());
+            }
+            let enc = OpensslEnc::new(rng.size(), KeyDerivation::BytesToKey(MessageDigest::Md5));
+            let _ = enc.decrypt(b"password", &data);
+        }
+
+        #[cfg(feature = "envelope")]
+        {
+            use crate::envelope::{self, Params};
+            let mut data =
+                envelope::seal(&Params::default(), &[1; 32], &[2; 16], &rng.bytes(40)).unwrap();
+            for _ in 0..rng.below(4) {
+                let i = rng.below(data.len());
+                data[i] = rng.next() as u8;
+            }
+            data.truncate(rng.below(data.len() + 1));
+            let _ = envelope::open(&[1; 32], &data);
+            let _ = envelope::open(&rng.bytes(40), &rng.bytes(100));
+        }
+
+        #[cfg(feature = "osu")]
+        {
+            use crate::osu;
+            let field = String::from_utf8_lossy(&rng.bytes(60)).into_owned();
+            let iv = String::from_utf8_lossy(&rng.bytes(60)).into_owned();
+            let version = String::from_utf8_lossy(&rng.bytes(12)).into_owned();
+            let _ = osu::decrypt_field(&field, &iv, &version);
+            let _ = osu::decrypt_score(&field, &iv, "20250101");
+            let _ = field.parse::<osu::ScoreSubmission>();
+            let _ = field.parse::<osu::ClientHash>();
+        }
+    }
+}
+
+#[test]
+fn test_new_const() {
+    use crate::{modes, rijndael::Rijndael};
+
+    static OSU: Rijndael = Rijndael::new_const(OSU_KEY, 32);
+    const AES: Rijndael = Rijndael::new_const(b"0123456789abcdef01234567", 16);
+
+    let runtime = Rijndael::new(OSU_KEY, 32).unwrap();
+    assert_eq!(OSU.k_e(), runtime.k_e());
+    assert_eq!(OSU.k_d(), runtime.k_d());
+    assert_eq!(OSU.rounds(), 14);
+    assert_eq!(
+        modes::cbc_decrypt(&OSU, &OSU_IV, &OSU_CRYPTED).unwrap(),
+        OSU_DECRYPTED
+    );
+    let runtime = Rijndael::new(b"0123456789abcdef01234567", 16).unwrap();
+    assert_eq!(AES.k_d(), runtime.k_d());
+    assert_eq!((AES.rounds(), AES.b_c()), (12, 4));
+}
+
+#[test]
+#[should_panic(expected = "key size must be 16, 24 or 32 bytes")]
+fn test_new_const_invalid() {
+    crate::rijndael::Rijndael::new_const(b"short", 16);
+}
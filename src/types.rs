@@ -0,0 +1,135 @@
+use std::ops::Deref;
+
+use crate::{constants::VALID, Errors, Size};
+
+// Byte slices checked against the sizes Rijndael accepts. The `&[u8]` APIs
+// build these first, so a wrong size is an error instead of a panic or a
+// silently truncated IV. IV arguments take `impl IntoIv`, an `Iv` or bytes.
+
+macro_rules! impl_bytes {
+    ($name: ident) => {
+        impl<'a> $name<'a> {
+            #[inline(always)]
+            pub fn as_bytes(&self) -> &'a [u8] {
+                self.0
+            }
+        }
+
+        impl Deref for $name<'_> {
+            type Target = [u8];
+
+            #[inline(always)]
+            fn deref(&self) -> &[u8] {
+                self.0
+            }
+        }
+
+        impl AsRef<[u8]> for $name<'_> {
+            #[inline(always)]
+            fn as_ref(&self) -> &[u8] {
+                self.0
+            }
+        }
+    };
+}
+
+/// A key of 16, 24 or 32 bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key<'a>(&'a [u8]);
+
+impl<'a> Key<'a> {
+    #[inline(always)]
+    pub fn new(key: &'a [u8]) -> Result<Self, Errors> {
+        if !VALID.contains(&key.len()) {
+            return Err(Errors::InvalidKeySize {
+                expected: Size::Rijndael,
+                actual: key.len(),
+            });
+        }
+        Ok(Self(key))
+    }
+}
+
+/// An IV of exactly one block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Iv<'a>(&'a [u8]);
+
+impl<'a> Iv<'a> {
+    #[inline(always)]
+    pub fn new(iv: &'a [u8], block_size: usize) -> Result<Self, Errors> {
+        if !VALID.contains(&block_size) {
+            return Err(Errors::InvalidBlockSize {
+                expected: Size::Rijndael,
+                actual: block_size,
+            });
+        }
+        if iv.len() != block_size {
+            return Err(Errors::InvalidIvSize {
+                expected: Size::Exactly(block_size),
+                actual: iv.len(),
+            });
+        }
+        Ok(Self(iv))
+    }
+}
+
+/// An `Iv`, or bytes checked with `Iv::new`. An `Iv` is checked again
+/// against `block_size`, it may have been built for another one.
+pub trait IntoIv<'a> {
+    fn into_iv(self, block_size: usize) -> Result<Iv<'a>, Errors>;
+}
+
+impl<'a> IntoIv<'a> for Iv<'a> {
+    #[inline(always)]
+    fn into_iv(self, block_size: usize) -> Result<Iv<'a>, Errors> {
+        Iv::new(self.0, block_size)
+    }
+}
+
+impl<'a> IntoIv<'a> for &'a [u8] {
+    #[inline(always)]
+    fn into_iv(self, block_size: usize) -> Result<Iv<'a>, Errors> {
+        Iv::new(self, block_size)
+    }
+}
+
+impl<'a, const N: usize> IntoIv<'a> for &'a [u8; N] {
+    #[inline(always)]
+    fn into_iv(self, block_size: usize) -> Result<Iv<'a>, Errors> {
+        Iv::new(self, block_size)
+    }
+}
+
+impl<'a> IntoIv<'a> for &'a Vec<u8> {
+    #[inline(always)]
+    fn into_iv(self, block_size: usize) -> Result<Iv<'a>, Errors> {
+        Iv::new(self, block_size)
+    }
+}
+
+/// A single block of input for `Rijndael::encrypt`/`decrypt`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Block<'a>(&'a [u8]);
+
+impl<'a> Block<'a> {
+    #[inline(always)]
+    pub fn new(block: &'a [u8], block_size: usize) -> Result<Self, Errors> {
+        if !VALID.contains(&block_size) {
+            return Err(Errors::InvalidBlockSize {
+                expected: Size::Rijndael,
+                actual: block_size,
+            });
+        }
+        if block.len() != block_size {
+            return Err(Errors::InvalidBlockSize {
+                expected: Size::Exactly(block_size),
+                actual: block.len(),
+            });
+        }
+        Ok(Self(block))
+    }
+}
+
+impl_bytes!(Key);
+impl_bytes!(Iv);
+impl_bytes!(Block);
@@ -0,0 +1,34 @@
+use wasm_bindgen::prelude::*;
+
+use crate::{impls::RijndaelCbc, paddings::PaddingKind};
+
+// `wasm-bindgen` bindings, byte arguments and results are `Uint8Array`s on
+// the JavaScript side and `Errors` are thrown as `Error` with their message.
+
+/// `new RijndaelCbc(key, blockSize, padding)`, `padding` is `"zero"` (osu!),
+/// `"pkcs7"`, `"ansix923"` or `"iso10126"`.
+#[wasm_bindgen(js_name = RijndaelCbc)]
+#[derive(Debug)]
+pub struct WasmRijndaelCbc(RijndaelCbc<PaddingKind>);
+
+#[wasm_bindgen(js_class = RijndaelCbc)]
+impl WasmRijndaelCbc {
+    #[wasm_bindgen(constructor)]
+    pub fn new(key: &[u8], block_size: usize, padding: &str) -> Result<WasmRijndaelCbc, JsError> {
+        let padding = PaddingKind::from_name(padding, block_size)?;
+        Ok(Self(RijndaelCbc::with_padding(key, padding)?))
+    }
+
+    #[wasm_bindgen(getter, js_name = blockSize)]
+    pub fn block_size(&self) -> usize {
+        self.0.rijndael.block_size()
+    }
+
+    pub fn encrypt(&self, iv: &[u8], data: &[u8]) -> Result<Vec<u8>, JsError> {
+        Ok(self.0.encrypt(iv, data.to_vec())?)
+    }
+
+    pub fn decrypt(&self, iv: &[u8], data: &[u8]) -> Result<Vec<u8>, JsError> {
+        Ok(self.0.decrypt(iv, data.to_vec())?)
+    }
+}
@@ -0,0 +1,63 @@
+use std::{env, path::PathBuf, process::Command};
+
+const MANIFEST_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+#[test]
+fn test_header_up_to_date() {
+    let config = cbindgen::Config::from_file(format!("{MANIFEST_DIR}/cbindgen.toml")).unwrap();
+    let mut generated = vec![];
+    cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(format!("{MANIFEST_DIR}/src/capi.rs"))
+        .generate()
+        .unwrap()
+        .write(&mut generated);
+    let committed = std::fs::read(format!("{MANIFEST_DIR}/include/simple_rijndael.h")).unwrap();
+    assert!(
+        generated == committed,
+        "include/simple_rijndael.h is stale, regenerate it with cbindgen (see cbindgen.toml)"
+    );
+}
+
+#[test]
+fn test_c_program() {
+    // the crate is only an rlib, build the staticlib in a separate target dir
+    // so the outer build lock is not held
+    let target_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("capi");
+    let status = Command::new(env!("CARGO"))
+        .args([
+            "rustc",
+            "--lib",
+            "--features",
+            "capi",
+            "--crate-type",
+            "staticlib",
+        ])
+        .arg("--manifest-path")
+        .arg(format!("{MANIFEST_DIR}/Cargo.toml"))
+        .arg("--target-dir")
+        .arg(&target_dir)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let out = target_dir.join("capi_test");
+    let status = Command::new(env::var("CC").unwrap_or_else(|_| "cc".into()))
+        .arg(format!("{MANIFEST_DIR}/tests/capi/main.c"))
+        .arg(format!("-I{MANIFEST_DIR}/include"))
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg("-o")
+        .arg(&out)
+        .arg(target_dir.join("debug/libsimple_rijndael.a"))
+        .args(["-lpthread", "-ldl", "-lm"])
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let output = Command::new(&out).output().unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(output.stdout, b"ok\n");
+}
@@ -0,0 +1,76 @@
+#include <stdio.h>
+#include <string.h>
+
+#include "simple_rijndael.h"
+
+#define CHECK(cond)                                                    \
+    do {                                                               \
+        if (!(cond)) {                                                 \
+            fprintf(stderr, "%s:%d: %s\n", __FILE__, __LINE__, #cond); \
+            return 1;                                                  \
+        }                                                              \
+    } while (0)
+
+static const uint8_t OSU_KEY[] = "osu!-scoreburgr---------20210520";
+static const uint8_t OSU_IV[32] = {
+    240, 124, 26, 154, 27, 186, 98, 170, 95, 190, 213, 103, 13, 128, 39, 59,
+    217, 84, 68, 144, 173, 93, 117, 132, 33, 213, 96, 154, 228, 231, 197, 162,
+};
+static const char OSU_SCORE[] =
+    "c51aee56bb5195244252d190baa54b49:PurePeace :815772625adad639735a044e8aba9360"
+    ":44:18:6:4:6:20:38645:26:False:F:64:False:0:210615064431:20210520";
+static const uint8_t OSU_CRYPTED_START[8] = {185, 209, 243, 33, 128, 187, 132, 199};
+
+int main(void) {
+    RijndaelCbcHandle *cipher = NULL;
+    uint8_t encrypted[256];
+    uint8_t decrypted[256];
+    size_t length = 0;
+    size_t score_len = strlen(OSU_SCORE);
+
+    CHECK(rijndael_cbc_new(OSU_KEY, 32, 32, RIJNDAEL_PADDING_ZERO, &cipher) == RIJNDAEL_OK);
+    CHECK(rijndael_cbc_encrypt_size(cipher, score_len) == 160);
+    CHECK(rijndael_cbc_encrypt_size(cipher, SIZE_MAX) == 0);
+    CHECK(rijndael_cbc_encrypt_size(cipher, SIZE_MAX - 31) == SIZE_MAX - 31);
+    CHECK(rijndael_cbc_encrypt(cipher, OSU_IV, 32, (const uint8_t *)OSU_SCORE, score_len,
+                               encrypted, sizeof(encrypted), &length) == RIJNDAEL_OK);
+    CHECK(length == 160);
+    CHECK(memcmp(encrypted, OSU_CRYPTED_START, 8) == 0);
+
+    CHECK(rijndael_cbc_decrypt_size(cipher, 160) >= score_len);
+    CHECK(rijndael_cbc_decrypt(cipher, OSU_IV, 32, encrypted, 160, decrypted, sizeof(decrypted),
+                               &length) == RIJNDAEL_OK);
+    CHECK(length == 160);
+    CHECK(memcmp(decrypted, OSU_SCORE, score_len) == 0);
+
+    /* too small output reports the required length */
+    CHECK(rijndael_cbc_encrypt(cipher, OSU_IV, 32, (const uint8_t *)OSU_SCORE, score_len,
+                               encrypted, 64, &length) == RIJNDAEL_ERR_BUFFER_TOO_SMALL);
+    CHECK(length == 160);
+    CHECK(rijndael_cbc_encrypt(cipher, OSU_IV, 16, (const uint8_t *)OSU_SCORE, score_len,
+                               encrypted, sizeof(encrypted), &length) == RIJNDAEL_ERR_IV_SIZE);
+    CHECK(rijndael_cbc_decrypt(cipher, OSU_IV, 32, encrypted, 15, decrypted, sizeof(decrypted),
+                               &length) == RIJNDAEL_ERR_DATA_SIZE);
+    rijndael_cbc_free(cipher);
+
+    CHECK(rijndael_cbc_new(OSU_KEY, 16, 16, RIJNDAEL_PADDING_PKCS7, &cipher) == RIJNDAEL_OK);
+    CHECK(rijndael_cbc_encrypt_size(cipher, 16) == 32);
+    CHECK(rijndael_cbc_encrypt_size(cipher, SIZE_MAX - 15) == 0);
+    CHECK(rijndael_cbc_encrypt(cipher, OSU_IV, 16, NULL, 0, encrypted, sizeof(encrypted),
+                               &length) == RIJNDAEL_OK);
+    CHECK(length == 16);
+    encrypted[15] ^= 1;
+    CHECK(rijndael_cbc_decrypt(cipher, OSU_IV, 16, encrypted, 16, decrypted, sizeof(decrypted),
+                               &length) == RIJNDAEL_ERR_PADDING);
+    rijndael_cbc_free(cipher);
+    rijndael_cbc_free(NULL);
+
+    CHECK(rijndael_cbc_new(OSU_KEY, 20, 16, RIJNDAEL_PADDING_ZERO, &cipher) == RIJNDAEL_ERR_KEY_SIZE);
+    CHECK(rijndael_cbc_new(OSU_KEY, 16, 20, RIJNDAEL_PADDING_ZERO, &cipher) == RIJNDAEL_ERR_BLOCK_SIZE);
+    CHECK(rijndael_cbc_new(OSU_KEY, 16, 16, 42, &cipher) == RIJNDAEL_ERR_PADDING);
+    CHECK(rijndael_cbc_new(NULL, 16, 16, RIJNDAEL_PADDING_ZERO, &cipher) == RIJNDAEL_ERR_NULL_POINTER);
+    CHECK(rijndael_cbc_encrypt_size(NULL, 16) == 0);
+
+    puts("ok");
+    return 0;
+}
@@ -0,0 +1,244 @@
+use assert_cmd::Command;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use tempfile::tempdir;
+
+const KEY: &str = "hex:31323820626974206b65792074657374";
+const IV: &str = "base64:MTI4IGJpdCBpdl8gdGVzdA==";
+const PLAIN: &[u8] = b"This string is used to test Pkcs7Padding";
+const CRYPTED: [u8; 48] = [
+    48, 107, 126, 49, 231, 35, 146, 225, 28, 112, 54, 23, 106, 236, 11, 57, 126, 253, 228, 202, 30,
+    190, 239, 164, 0, 255, 148, 52, 176, 36, 32, 241, 163, 213, 33, 243, 202, 7, 221, 94, 84, 37,
+    77, 160, 242, 223, 108, 162,
+];
+const OSU_IV: &str = "8Hwamhu6YqpfvtVnDYAnO9lURJCtXXWEIdVgmuTnxaI=";
+const OSU_SCORE: &str = "c51aee56bb5195244252d190baa54b49:PurePeace :815772625adad639735a044e8aba9360:44:18:6:4:6:20:38645:26:False:F:64:False:0:210615064431:20210520";
+
+fn rijndael() -> Command {
+    Command::cargo_bin("rijndael").unwrap()
+}
+
+#[test]
+fn test_files() {
+    let dir = tempdir().unwrap();
+    let plain = dir.path().join("plain");
+    let crypted = dir.path().join("crypted");
+    let decrypted = dir.path().join("decrypted");
+    std::fs::write(&plain, PLAIN).unwrap();
+
+    rijndael()
+        .args([
+            "encrypt",
+            "--key",
+            KEY,
+            "--iv",
+            IV,
+            "--key-size",
+            "16",
+            "-o",
+        ])
+        .arg(&crypted)
+        .arg(&plain)
+        .assert()
+        .success();
+    assert_eq!(std::fs::read(&crypted).unwrap(), CRYPTED);
+
+    let key_file = dir.path().join("key");
+    std::fs::write(&key_file, b"128 bit key test").unwrap();
+    rijndael()
+        .arg("decrypt")
+        .arg(format!("--key=file:{}", key_file.display()))
+        .args(["--iv", IV, "--padding", "pkcs7", "-o"])
+        .arg(&decrypted)
+        .arg(&crypted)
+        .assert()
+        .success();
+    assert_eq!(std::fs::read(&decrypted).unwrap(), PLAIN);
+}
+
+#[test]
+fn test_stdin() {
+    rijndael()
+        .args(["decrypt", "--key", KEY, "--iv", IV])
+        .write_stdin(CRYPTED)
+        .assert()
+        .success()
+        .stdout(PLAIN);
+}
+
+#[test]
+fn test_osu_score() {
+    let output = rijndael()
+        .args(["encrypt", "--osu-score", "--osu-version", "20210520"])
+        .args(["--iv", &format!("base64:{}", OSU_IV)])
+        .write_stdin(OSU_SCORE)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    assert!(STANDARD.decode(&output).is_ok());
+
+    rijndael()
+        .args(["decrypt", "--osu-score", "--osu-version", "20210520"])
+        .args(["--iv", &format!("base64:{}", OSU_IV)])
+        .write_stdin(output)
+        .assert()
+        .success()
+        .stdout(OSU_SCORE);
+}
+
+#[test]
+fn test_osu_score_client_flags() {
+    // the spaces after the version are the client flags, only the line ending
+    // of the input is dropped
+    let score = format!("{}   ", OSU_SCORE);
+    let output = rijndael()
+        .args(["encrypt", "--osu-score", "--osu-version", "20210520"])
+        .args(["--iv", &format!("base64:{}", OSU_IV)])
+        .write_stdin(format!("{}\r\n", score))
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    rijndael()
+        .args(["decrypt", "--osu-score", "--osu-version", "20210520"])
+        .args(["--iv", &format!("base64:{}", OSU_IV)])
+        .write_stdin(output)
+        .assert()
+        .success()
+        .stdout(score);
+}
+
+#[test]
+fn test_errors() {
+    rijndael()
+        .args(["encrypt", "--key", KEY, "--iv", IV, "--key-size", "32"])
+        .write_stdin(PLAIN)
+        .assert()
+        .failure()
+        .stderr("rijndael: key is 16 bytes, expected 32\n");
+    rijndael()
+        .args(["encrypt", "--key", KEY, "--iv", "hex:00"])
+        .write_stdin(PLAIN)
+        .assert()
+        .failure();
+    rijndael()
+        .args(["decrypt", "--key", KEY, "--iv", IV])
+        .write_stdin(&CRYPTED[..20])
+        .assert()
+        .failure();
+    rijndael()
+        .args(["encrypt", "--osu-score", "--iv", IV])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_modes() {
+    use simple_rijndael::cipher::Cipher;
+
+    for (mode, iv) in [("ecb", None), ("cfb", Some(IV)), ("ofb", Some(IV))] {
+        let cipher = Cipher::new(&format!("rijndael-128-{}", mode), b"128 bit key test").unwrap();
+        let iv_bytes = match iv {
+            Some(_) => b"128 bit iv_ test".to_vec(),
+            None => Vec::new(),
+        };
+        let expected = cipher.encrypt(&iv_bytes, PLAIN).unwrap();
+
+        let mut encrypt = rijndael();
+        encrypt.args(["encrypt", "--mode", mode, "--key", KEY]);
+        let mut decrypt = rijndael();
+        decrypt.args(["decrypt", "--mode", mode, "--key", KEY]);
+        if let Some(iv) = iv {
+            encrypt.args(["--iv", iv]);
+            decrypt.args(["--iv", iv]);
+        }
+        encrypt
+            .write_stdin(PLAIN)
+            .assert()
+            .success()
+            .stdout(expected.clone());
+        decrypt
+            .write_stdin(expected)
+            .assert()
+            .success()
+            .stdout(PLAIN);
+    }
+
+    rijndael()
+        .args(["encrypt", "--mode", "ecb", "--key", KEY, "--iv", IV])
+        .write_stdin(PLAIN)
+        .assert()
+        .failure()
+        .stderr("rijndael: ecb mode takes no iv\n");
+    rijndael()
+        .args(["encrypt", "--mode", "cfb", "--key", KEY])
+        .write_stdin(PLAIN)
+        .assert()
+        .failure()
+        .stderr("rijndael: --iv is required in cfb mode\n");
+    rijndael()
+        .args([
+            "encrypt",
+            "--mode",
+            "ofb",
+            "--padding",
+            "pkcs7",
+            "--key",
+            KEY,
+            "--iv",
+            IV,
+        ])
+        .write_stdin(PLAIN)
+        .assert()
+        .failure()
+        .stderr("rijndael: ofb mode takes no padding\n");
+    rijndael()
+        .args(["encrypt", "--mode", "ctr", "--key", KEY, "--iv", IV])
+        .assert()
+        .failure();
+    rijndael()
+        .args(["encrypt", "--block-size", "20", "--key", KEY, "--iv", IV])
+        .write_stdin(PLAIN)
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_paddings() {
+    use simple_rijndael::cipher::Cipher;
+
+    for padding in ["none", "zero", "pkcs7", "ansix923", "iso10126"] {
+        let input = match padding {
+            "none" => &PLAIN[..32],
+            _ => PLAIN,
+        };
+        let output = rijndael()
+            .args(["encrypt", "--padding", padding, "--key", KEY, "--iv", IV])
+            .write_stdin(input)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        // iso10126 pads with random bytes, zero padding is kept by decrypt
+        let cipher = Cipher::new(
+            &format!("rijndael-128-cbc/{}", padding),
+            b"128 bit key test",
+        )
+        .unwrap();
+        if padding != "iso10126" {
+            assert_eq!(output, cipher.encrypt(b"128 bit iv_ test", input).unwrap());
+        }
+        let decrypted = cipher.decrypt(b"128 bit iv_ test", &output).unwrap();
+        assert!(decrypted.starts_with(input));
+        rijndael()
+            .args(["decrypt", "--padding", padding, "--key", KEY, "--iv", IV])
+            .write_stdin(output)
+            .assert()
+            .success()
+            .stdout(decrypted);
+    }
+}
@@ -0,0 +1,111 @@
+//! Compares `Rijndael` with the `aes` crate (16 byte blocks) and with the
+//! literal implementation in `reference` (all block sizes).
+
+mod reference;
+
+use aes::cipher::{generic_array::GenericArray, BlockDecrypt, BlockEncrypt, KeyInit};
+use rand_chacha::{
+    rand_core::{RngCore, SeedableRng},
+    ChaCha8Rng,
+};
+use reference::Reference;
+use simple_rijndael::{impls::RijndaelCbc, paddings::ZeroPadding, rijndael::Rijndael};
+
+const SIZES: [usize; 3] = [16, 24, 32];
+
+fn random(rng: &mut ChaCha8Rng, size: usize) -> Vec<u8> {
+    let mut bytes = vec![0; size];
+    rng.fill_bytes(&mut bytes);
+    bytes
+}
+
+fn aes_encrypt(key: &[u8], block: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let mut encrypted = GenericArray::clone_from_slice(block);
+    let mut decrypted = GenericArray::clone_from_slice(block);
+    macro_rules! run {
+        ($aes: ty) => {{
+            let cipher = <$aes>::new_from_slice(key).unwrap();
+            cipher.encrypt_block(&mut encrypted);
+            cipher.decrypt_block(&mut decrypted);
+        }};
+    }
+    match key.len() {
+        16 => run!(aes::Aes128),
+        24 => run!(aes::Aes192),
+        _ => run!(aes::Aes256),
+    }
+    (encrypted.to_vec(), decrypted.to_vec())
+}
+
+#[test]
+fn test_against_aes() {
+    let mut rng = ChaCha8Rng::seed_from_u64(0xae5);
+    for key_size in SIZES {
+        for _ in 0..500 {
+            let key = random(&mut rng, key_size);
+            let block = random(&mut rng, 16);
+            let rijndael = Rijndael::new(&key, 16).unwrap();
+            let (encrypted, decrypted) = aes_encrypt(&key, &block);
+            assert_eq!(rijndael.encrypt(&block).unwrap(), encrypted);
+            assert_eq!(rijndael.decrypt(&block).unwrap(), decrypted);
+        }
+    }
+}
+
+#[test]
+fn test_against_reference() {
+    let mut rng = ChaCha8Rng::seed_from_u64(0x41e5);
+    for block_size in SIZES {
+        for key_size in SIZES {
+            for _ in 0..20 {
+                let key = random(&mut rng, key_size);
+                let block = random(&mut rng, block_size);
+                let rijndael = Rijndael::new(&key, block_size).unwrap();
+                let reference = Reference::new(&key, block_size);
+                let encrypted = reference.encrypt(&block);
+                assert_eq!(rijndael.encrypt(&block).unwrap(), encrypted);
+                assert_eq!(rijndael.decrypt(&block).unwrap(), reference.decrypt(&block));
+                assert_eq!(reference.decrypt(&encrypted), block);
+            }
+        }
+    }
+}
+
+/// The reference itself against the FIPS-197 appendix C vectors and the
+/// osu! score vector (256 bit block).
+#[test]
+fn test_reference_vectors() {
+    let block: Vec<u8> = (0..16).map(|i| i * 0x11).collect();
+    for (key_size, expected) in [
+        (16, "69c4e0d86a7b0430d8cdb78070b4c55a"),
+        (24, "dda97ca4864cdfe06eaf70a0ec0d7191"),
+        (32, "8ea2b7ca516745bfeafc49904b496089"),
+    ] {
+        let key: Vec<u8> = (0..key_size as u8).collect();
+        let encrypted = Reference::new(&key, 16).encrypt(&block);
+        assert_eq!(hex(&encrypted), expected);
+    }
+
+    let key = b"osu!-scoreburgr---------20210520";
+    let iv: [u8; 32] = [
+        240, 124, 26, 154, 27, 186, 98, 170, 95, 190, 213, 103, 13, 128, 39, 59, 217, 84, 68, 144,
+        173, 93, 117, 132, 33, 213, 96, 154, 228, 231, 197, 162,
+    ];
+    let plaintext = b"c51aee56bb5195244252d190baa54b49";
+    let reference = Reference::new(key, 32);
+    let chained: Vec<u8> = plaintext.iter().zip(iv).map(|(p, v)| p ^ v).collect();
+    let expected = RijndaelCbc::<ZeroPadding>::new(key, 32)
+        .unwrap()
+        .encrypt(&iv, plaintext.to_vec())
+        .unwrap();
+    assert_eq!(reference.encrypt(&chained), expected);
+    assert_eq!(
+        expected[..4],
+        [185, 209, 243, 33],
+        "first bytes of the osu! vector"
+    );
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
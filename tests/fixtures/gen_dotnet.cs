@@ -0,0 +1,93 @@
+// Generates dotnet.txt:
+// `block_size mode feedback_size padding key iv plaintext ciphertext` (sizes
+// in bits, bytes in hex) with RijndaelManaged. Keys and IVs (as long as the
+// block) come from Rfc2898DeriveBytes("password", "saltsalt", 1000), key
+// first. Needs .NET Framework or Mono, RijndaelManaged on .NET Core only has
+// 128 bit blocks:
+//
+//     mcs gen_dotnet.cs && mono gen_dotnet.exe > dotnet.txt
+//     csc gen_dotnet.cs && gen_dotnet.exe > dotnet.txt
+//
+// ISO10126 fills with random bytes, those lines are only decrypted by the
+// tests.
+
+using System;
+using System.Security.Cryptography;
+using System.Text;
+
+static class GenDotnet
+{
+    static readonly string[] Plaintexts =
+    {
+        "",
+        "Rijndael",
+        "exactly thirty-two bytes of data",
+        "The quick brown fox jumps over the lazy dog",
+    };
+
+    static readonly PaddingMode[] Paddings =
+    {
+        PaddingMode.None,
+        PaddingMode.PKCS7,
+        PaddingMode.Zeros,
+        PaddingMode.ANSIX923,
+        PaddingMode.ISO10126,
+    };
+
+    static string Hex(byte[] bytes)
+    {
+        return BitConverter.ToString(bytes).Replace("-", "").ToLowerInvariant();
+    }
+
+    static void Main()
+    {
+        Console.WriteLine("# RijndaelManaged on CLR " + Environment.Version + ", generated by gen_dotnet.cs");
+        foreach (int bs in new[] { 16, 24, 32 })
+        {
+            byte[] key, iv;
+            using (var derive = new Rfc2898DeriveBytes("password", Encoding.ASCII.GetBytes("saltsalt"), 1000))
+            {
+                key = derive.GetBytes(bs);
+                iv = derive.GetBytes(bs);
+            }
+            var modes = new[]
+            {
+                Tuple.Create(CipherMode.CBC, bs),
+                Tuple.Create(CipherMode.ECB, bs),
+                Tuple.Create(CipherMode.CFB, 1),
+                Tuple.Create(CipherMode.CFB, 8),
+                Tuple.Create(CipherMode.CFB, bs),
+            };
+            foreach (var mode in modes)
+            {
+                int size = mode.Item1 == CipherMode.CFB ? mode.Item2 : bs;
+                foreach (PaddingMode padding in Paddings)
+                {
+                    foreach (string text in Plaintexts)
+                    {
+                        byte[] data = Encoding.ASCII.GetBytes(text);
+                        if (padding == PaddingMode.None && data.Length % size != 0)
+                        {
+                            continue;
+                        }
+                        using (var rijndael = new RijndaelManaged())
+                        {
+                            rijndael.BlockSize = bs * 8;
+                            rijndael.Mode = mode.Item1;
+                            rijndael.FeedbackSize = mode.Item2 * 8;
+                            rijndael.Padding = padding;
+                            rijndael.Key = key;
+                            rijndael.IV = iv;
+                            using (var encryptor = rijndael.CreateEncryptor())
+                            {
+                                byte[] crypted = encryptor.TransformFinalBlock(data, 0, data.Length);
+                                Console.WriteLine(string.Join(" ", bs * 8, mode.Item1, mode.Item2 * 8,
+                                    padding, Hex(key), Hex(iv), Hex(data), Hex(crypted)));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,65 @@
+<?php
+// Generates mcrypt.txt: `algorithm mode key iv plaintext ciphertext` (hex)
+// with PHP's mcrypt extension (bundled up to PHP 7.1, PECL mcrypt later):
+//
+//     php gen_mcrypt.php > mcrypt.txt
+//
+// The inputs are the NIST SP 800-38A ones, with one block of IV per block
+// size and 18 bytes of data in the 8 bit cfb/ofb modes. The last cases cover
+// mcrypt's zero padding: unaligned and empty data.
+
+$keys = [
+    '2b7e151628aed2a6abf7158809cf4f3c',
+    '8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b',
+    '603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4',
+];
+$plain = hex2bin(
+    '6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51'
+    . '30c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710'
+);
+$algorithms = [
+    'rijndael-128' => MCRYPT_RIJNDAEL_128,
+    'rijndael-192' => MCRYPT_RIJNDAEL_192,
+    'rijndael-256' => MCRYPT_RIJNDAEL_256,
+];
+$modes = ['ecb', 'cbc', 'cfb', 'ofb', 'ncfb', 'nofb'];
+
+function line($algorithm, $mode, $key, $iv, $data)
+{
+    global $algorithms;
+    $crypted = mcrypt_encrypt($algorithms[$algorithm], $key, $data, $mode, $iv);
+    if ($crypted === false) {
+        fwrite(STDERR, "mcrypt_encrypt failed for $algorithm $mode\n");
+        exit(1);
+    }
+    echo implode(' ', [
+        $algorithm,
+        $mode,
+        bin2hex($key),
+        bin2hex($iv),
+        bin2hex($data),
+        bin2hex($crypted),
+    ]), "\n";
+}
+
+echo "# PHP ", PHP_VERSION, " mcrypt, generated by gen_mcrypt.php\n";
+foreach ($algorithms as $algorithm => $cipher) {
+    $block_size = mcrypt_get_block_size($cipher, 'ecb');
+    $iv = '';
+    for ($i = 0; $i < $block_size; $i++) {
+        $iv .= chr($i);
+    }
+    foreach ($modes as $mode) {
+        $mode_iv = $mode === 'ecb' ? '' : $iv;
+        $data = in_array($mode, ['cfb', 'ofb']) ? substr($plain, 0, 18) : $plain;
+        foreach ($keys as $key) {
+            line($algorithm, $mode, hex2bin($key), $mode_iv, $data);
+        }
+    }
+    foreach (['ecb', 'cbc'] as $mode) {
+        $mode_iv = $mode === 'ecb' ? '' : $iv;
+        foreach ([substr($plain, 0, 21), ''] as $data) {
+            line($algorithm, $mode, hex2bin($keys[0]), $mode_iv, $data);
+        }
+    }
+}
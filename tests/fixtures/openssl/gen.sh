@@ -0,0 +1,9 @@
+#!/bin/sh
+# Regenerates the `openssl enc` fixtures (random salts, password "secret").
+set -e
+cd "$(dirname "$0")"
+openssl enc -aes-256-cbc -md md5 -pass pass:secret -in plain.txt -out aes-256-cbc-md5.enc
+openssl enc -aes-128-cbc -md md5 -pass pass:secret -in plain.txt -out aes-128-cbc-md5.enc
+openssl enc -aes-256-cbc -md sha256 -pass pass:secret -in plain.txt -out aes-256-cbc-sha256.enc
+openssl enc -aes-256-cbc -pbkdf2 -iter 10000 -pass pass:secret -in plain.txt -out aes-256-cbc-pbkdf2.enc
+openssl enc -aes-192-cbc -pbkdf2 -pass pass:secret -a -in plain.txt -out aes-192-cbc-pbkdf2.b64
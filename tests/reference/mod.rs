@@ -0,0 +1,167 @@
+//! Straightforward Rijndael for any block and key size of 16, 24 or 32 bytes,
+//! written after the AES proposal: the S-box is computed from the field
+//! inverse and affine map, and every round step is applied literally to a
+//! 4 x Nb state. Slow, only meant to check the table-driven implementation.
+
+use std::sync::OnceLock;
+
+/// Multiplication in GF(2^8) modulo x^8 + x^4 + x^3 + x + 1.
+fn mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    while b != 0 {
+        if b & 1 != 0 {
+            product ^= a;
+        }
+        let carry = a & 0x80;
+        a <<= 1;
+        if carry != 0 {
+            a ^= 0x1b;
+        }
+        b >>= 1;
+    }
+    product
+}
+
+fn inverse(a: u8) -> u8 {
+    if a == 0 {
+        return 0;
+    }
+    (1..=255).find(|b| mul(a, *b) == 1).unwrap()
+}
+
+fn sub_byte(a: u8) -> u8 {
+    let b = inverse(a);
+    b ^ b.rotate_left(1) ^ b.rotate_left(2) ^ b.rotate_left(3) ^ b.rotate_left(4) ^ 0x63
+}
+
+/// S-box and inverse S-box, computed once.
+fn boxes() -> &'static ([u8; 256], [u8; 256]) {
+    static BOXES: OnceLock<([u8; 256], [u8; 256])> = OnceLock::new();
+    BOXES.get_or_init(|| {
+        let mut sbox = [0; 256];
+        let mut inv_sbox = [0; 256];
+        for i in 0..256 {
+            sbox[i] = sub_byte(i as u8);
+            inv_sbox[sbox[i] as usize] = i as u8;
+        }
+        (sbox, inv_sbox)
+    })
+}
+
+pub struct Reference {
+    nb: usize,
+    rounds: usize,
+    sbox: [u8; 256],
+    inv_sbox: [u8; 256],
+    /// Round keys, `rounds + 1` of `nb` words of 4 bytes each.
+    w: Vec<[u8; 4]>,
+}
+
+type State = Vec<[u8; 4]>;
+
+impl Reference {
+    pub fn new(key: &[u8], block_size: usize) -> Self {
+        let nb = block_size / 4;
+        let nk = key.len() / 4;
+        let rounds = nb.max(nk) + 6;
+        let (sbox, inv_sbox) = *boxes();
+
+        let mut w: Vec<[u8; 4]> = key.chunks(4).map(|c| [c[0], c[1], c[2], c[3]]).collect();
+        let mut rcon = 1u8;
+        for i in nk..nb * (rounds + 1) {
+            let mut temp = w[i - 1];
+            if i % nk == 0 {
+                temp.rotate_left(1);
+                temp = temp.map(|b| sbox[b as usize]);
+                temp[0] ^= rcon;
+                rcon = mul(rcon, 2);
+            } else if nk > 6 && i % nk == 4 {
+                temp = temp.map(|b| sbox[b as usize]);
+            }
+            let previous = w[i - nk];
+            w.push([0, 1, 2, 3].map(|j| previous[j] ^ temp[j]));
+        }
+        Self {
+            nb,
+            rounds,
+            sbox,
+            inv_sbox,
+            w,
+        }
+    }
+
+    /// Row shift offsets for rows 1 to 3.
+    fn shifts(&self) -> [usize; 3] {
+        match self.nb {
+            8 => [1, 3, 4],
+            _ => [1, 2, 3],
+        }
+    }
+
+    fn add_round_key(&self, state: &mut State, round: usize) {
+        for (c, column) in state.iter_mut().enumerate() {
+            for (r, byte) in column.iter_mut().enumerate() {
+                *byte ^= self.w[round * self.nb + c][r];
+            }
+        }
+    }
+
+    fn shift_rows(&self, state: &mut State, inverse: bool) {
+        for (row, shift) in self.shifts().into_iter().enumerate() {
+            let r = row + 1;
+            let bytes: Vec<u8> = state.iter().map(|column| column[r]).collect();
+            for (c, column) in state.iter_mut().enumerate() {
+                let from = match inverse {
+                    false => (c + shift) % self.nb,
+                    true => (c + self.nb - shift) % self.nb,
+                };
+                column[r] = bytes[from];
+            }
+        }
+    }
+
+    fn mix_columns(state: &mut State, matrix: [u8; 4]) {
+        for column in state.iter_mut() {
+            let a = *column;
+            for (r, byte) in column.iter_mut().enumerate() {
+                *byte = (0..4).fold(0, |acc, i| acc ^ mul(matrix[(i + 4 - r) % 4], a[i]));
+            }
+        }
+    }
+
+    fn to_state(&self, block: &[u8]) -> State {
+        block.chunks(4).map(|c| [c[0], c[1], c[2], c[3]]).collect()
+    }
+
+    pub fn encrypt(&self, block: &[u8]) -> Vec<u8> {
+        let mut state = self.to_state(block);
+        self.add_round_key(&mut state, 0);
+        for round in 1..=self.rounds {
+            for column in state.iter_mut() {
+                *column = column.map(|b| self.sbox[b as usize]);
+            }
+            self.shift_rows(&mut state, false);
+            if round != self.rounds {
+                Self::mix_columns(&mut state, [2, 3, 1, 1]);
+            }
+            self.add_round_key(&mut state, round);
+        }
+        state.concat()
+    }
+
+    pub fn decrypt(&self, block: &[u8]) -> Vec<u8> {
+        let mut state = self.to_state(block);
+        self.add_round_key(&mut state, self.rounds);
+        for round in (0..self.rounds).rev() {
+            self.shift_rows(&mut state, true);
+            for column in state.iter_mut() {
+                *column = column.map(|b| self.inv_sbox[b as usize]);
+            }
+            self.add_round_key(&mut state, round);
+            if round != 0 {
+                Self::mix_columns(&mut state, [14, 11, 13, 9]);
+            }
+        }
+        state.concat()
+    }
+}
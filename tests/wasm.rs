@@ -0,0 +1,56 @@
+//! Run under node with `wasm-bindgen-test-runner` (from `wasm-bindgen-cli`):
+//! `CARGO_TARGET_WASM32_UNKNOWN_UNKNOWN_RUNNER=wasm-bindgen-test-runner cargo test
+//! --target wasm32-unknown-unknown --features wasm --test wasm`
+#![cfg(target_arch = "wasm32")]
+
+use simple_rijndael::wasm::WasmRijndaelCbc;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+const OSU_KEY: &[u8; 32] = b"osu!-scoreburgr---------20210520";
+const OSU_IV: [u8; 32] = [
+    240, 124, 26, 154, 27, 186, 98, 170, 95, 190, 213, 103, 13, 128, 39, 59, 217, 84, 68, 144, 173,
+    93, 117, 132, 33, 213, 96, 154, 228, 231, 197, 162,
+];
+const OSU_SCORE: &[u8] = b"c51aee56bb5195244252d190baa54b49:PurePeace :815772625adad639735a044e8aba9360:44:18:6:4:6:20:38645:26:False:F:64:False:0:210615064431:20210520";
+const OSU_CRYPTED_START: [u8; 8] = [185, 209, 243, 33, 128, 187, 132, 199];
+
+#[wasm_bindgen_test]
+fn test_osu_round_trip() {
+    let cipher = WasmRijndaelCbc::new(OSU_KEY, 32, "zero").unwrap();
+    assert_eq!(cipher.block_size(), 32);
+    let encrypted = cipher.encrypt(&OSU_IV, OSU_SCORE).unwrap();
+    assert_eq!(encrypted.len(), 160);
+    assert_eq!(encrypted[..8], OSU_CRYPTED_START);
+    let decrypted = cipher.decrypt(&OSU_IV, &encrypted).unwrap();
+    assert_eq!(&decrypted[..OSU_SCORE.len()], OSU_SCORE);
+}
+
+#[wasm_bindgen_test]
+fn test_pkcs7_round_trip() {
+    let cipher = WasmRijndaelCbc::new(&[1; 16], 24, "pkcs7").unwrap();
+    let encrypted = cipher.encrypt(&[2; 24], b"wasm").unwrap();
+    assert_eq!(encrypted.len(), 24);
+    assert_eq!(cipher.decrypt(&[2; 24], &encrypted).unwrap(), b"wasm");
+}
+
+#[wasm_bindgen_test]
+fn test_errors() {
+    assert!(WasmRijndaelCbc::new(&[1; 16], 16, "pkcs5").is_err());
+    assert!(WasmRijndaelCbc::new(&[1; 20], 16, "zero").is_err());
+    let cipher = WasmRijndaelCbc::new(&[1; 16], 16, "pkcs7").unwrap();
+    assert!(cipher.encrypt(&[2; 8], b"short iv").is_err());
+    assert!(cipher.decrypt(&[2; 16], &[0; 15]).is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_paddings() {
+    for padding in ["zero", "pkcs7", "ansix923", "iso10126"] {
+        let cipher = WasmRijndaelCbc::new(&[3; 32], 32, padding).unwrap();
+        let encrypted = cipher.encrypt(&[4; 32], b"padding").unwrap();
+        assert_eq!(encrypted.len(), 32);
+        assert_eq!(
+            &cipher.decrypt(&[4; 32], &encrypted).unwrap()[..7],
+            b"padding"
+        );
+    }
+}